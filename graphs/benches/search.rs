@@ -0,0 +1,64 @@
+extern crate criterion;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use graphs::search::{bfs, bidirectional_bfs, bidirectional_dijkstra, dijkstra};
+use graphs::Graph;
+
+const SIDE: u64 = 200;
+
+/// An undirected `SIDE x SIDE` grid, with weights depending on the position of the edge.
+fn make_grid_igraph() -> Graph<u64, u64> {
+  let mut g = Graph::new();
+
+  for row in 0..SIDE {
+    for col in 0..SIDE {
+      let vid = row * SIDE + col;
+      g.push_vid(vid);
+
+      if col + 1 < SIDE {
+        g.push_undirected_edge(vid, vid + 1, 1 + (row + col) % 7);
+      }
+      if row + 1 < SIDE {
+        g.push_undirected_edge(vid, vid + SIDE, 1 + (row * col) % 5);
+      }
+    }
+  }
+
+  g
+}
+
+// the endpoints are far from the borders of the grid, which would otherwise limit the area
+// explored by the plain searches, hiding the advantage of the bidirectional ones
+const START: u64 = SIDE / 2 * SIDE + 3 * SIDE / 8;
+const GOAL: u64 = SIDE / 2 * SIDE + 5 * SIDE / 8;
+
+// the grid is built inside of each benchmark, since criterion needs `'static` closures
+fn unweighted(c: &mut Criterion) {
+  c.bench_function("search (bfs)", |b| {
+    let g = make_grid_igraph();
+    b.iter(|| bfs(&g, black_box(&START), |vid, _| *vid == GOAL, &mut ()).found)
+  });
+  c.bench_function("search (bidirectional_bfs)", |b| {
+    let g = make_grid_igraph();
+    b.iter(|| bidirectional_bfs(&g, black_box(&START), &GOAL, None, &mut ()))
+  });
+}
+
+fn weighted(c: &mut Criterion) {
+  c.bench_function("search (dijkstra)", |b| {
+    let g = make_grid_igraph();
+    b.iter(|| dijkstra(&g, black_box(&START), |w| *w, Some(&GOAL), &mut ()).found)
+  });
+  c.bench_function("search (bidirectional_dijkstra)", |b| {
+    let g = make_grid_igraph();
+    b.iter(|| bidirectional_dijkstra(&g, black_box(&START), |w| *w, &GOAL, None, &mut ()))
+  });
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().sample_size(10);
+  targets = unweighted, weighted
+}
+
+criterion_main!(benches);
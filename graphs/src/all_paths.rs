@@ -0,0 +1,375 @@
+//! Enumerating and counting all paths between two vertices, where a `VisitPolicy` decides
+//! how many times each vertex can appear on a path.
+//!
+//! All paths stop at the target: it can only be the last vertex of a path. If the policy allows
+//! unlimited visits to vertices forming a cycle, there are infinitely many paths, so the iterator
+//! never ends, and counting doesn't terminate.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Graph, Path};
+
+/// Decides how many times each vertex can be visited by a single path.
+pub trait VisitPolicy<VId> {
+  /// The maximum number of times `vid` can appear on a path, or `None` if it's unlimited.
+  fn limit(&self, vid: &VId) -> Option<usize>;
+
+  /// How many times a single path can visit a vertex over its limit. Defaults to 0.
+  fn exceptions(&self) -> usize {
+    0
+  }
+
+  /// Tells if an exception can be used to visit `vid` over its limit. Defaults to `true`.
+  fn allows_exception(&self, _vid: &VId) -> bool {
+    true
+  }
+}
+
+/// Each vertex can be visited at most once, i.e. only simple paths are allowed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Simple;
+
+impl<VId> VisitPolicy<VId> for Simple {
+  fn limit(&self, _vid: &VId) -> Option<usize> {
+    Some(1)
+  }
+}
+
+/// A function from a vertex id to its limit works as a policy without exceptions.
+impl<VId, F> VisitPolicy<VId> for F
+where
+  F: Fn(&VId) -> Option<usize>,
+{
+  fn limit(&self, vid: &VId) -> Option<usize> {
+    self(vid)
+  }
+}
+
+/// Returns an iterator over all paths from `from` to `to` allowed by the `policy`,
+/// found by depth-first search. The cost of each path is its number of edges.
+///
+/// Paths are different if they follow different edges, so parallel edges
+/// produce paths with the same vertices.
+pub fn all_paths<'a, VId, E, V, P>(
+  g: &'a Graph<VId, E, V>,
+  from: &'a VId,
+  to: &'a VId,
+  policy: P,
+) -> AllPaths<'a, VId, E, V, P>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  P: VisitPolicy<VId>,
+{
+  let mut paths = AllPaths {
+    g,
+    to,
+    policy,
+    path: vec![],
+    visits: Visits::default(),
+    started: false,
+  };
+  paths.enter(from);
+  paths
+}
+
+/// Iterator over the paths allowed by a `VisitPolicy`, returned by `all_paths`.
+pub struct AllPaths<'a, VId, E, V, P> {
+  g: &'a Graph<VId, E, V>,
+  to: &'a VId,
+  policy: P,
+  // the current path, with the index of the next edge to follow from each vertex
+  path: Vec<(&'a VId, usize)>,
+  visits: Visits<'a, VId>,
+  started: bool,
+}
+
+impl<'a, VId, E, V, P> AllPaths<'a, VId, E, V, P>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  P: VisitPolicy<VId>,
+{
+  /// Extends the current path with `vid`, if the policy allows it.
+  fn enter(&mut self, vid: &'a VId) -> bool {
+    let entered = self.visits.enter(vid, &self.policy);
+    if entered {
+      self.path.push((vid, 0));
+    }
+    entered
+  }
+
+  fn leave(&mut self) {
+    if let Some((vid, _next_edge)) = self.path.pop() {
+      self.visits.leave(vid);
+    }
+  }
+
+  fn current_path(&self) -> Path<&'a VId, &'a E> {
+    let mut path = Path::new(self.path[0].0);
+    for window in self.path.windows(2) {
+      let ((from, next_edge), (to, _)) = (window[0], window[1]);
+      // the edge leading to `to` is the last one followed from `from`
+      let (_to, edge) = &self.g.incident_edges(from).unwrap()[next_edge - 1];
+      path.push(edge, to, 1);
+    }
+    path
+  }
+}
+
+impl<'a, VId, E, V, P> Iterator for AllPaths<'a, VId, E, V, P>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  P: VisitPolicy<VId>,
+{
+  type Item = Path<&'a VId, &'a E>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let reached_target = matches!(self.path.last(), Some(&(vid, _)) if vid == self.to);
+    if !self.started {
+      self.started = true;
+      if reached_target {
+        return Some(self.current_path());
+      }
+    } else if reached_target {
+      self.leave();
+    }
+
+    while let Some(&(curr, next_edge)) = self.path.last() {
+      match self.g.incident_edges(curr).and_then(|edges| edges.get(next_edge)) {
+        None => self.leave(),
+        Some((next, _edge)) => {
+          if let Some((_, next_edge)) = self.path.last_mut() {
+            *next_edge += 1;
+          }
+
+          if self.enter(next) && next == self.to {
+            return Some(self.current_path());
+          }
+        }
+      }
+    }
+
+    None
+  }
+}
+
+/// Counts the paths from `from` to `to` allowed by the `policy`, i.e. the paths `all_paths` would return.
+///
+/// The counts of paths from each vertex are memoized for each combination of visits to the limited
+/// vertices, which is much faster than enumerating the paths when many of them share the same suffixes.
+pub fn count_paths<VId, E, V, P>(g: &Graph<VId, E, V>, from: &VId, to: &VId, policy: P) -> usize
+where
+  VId: Eq + Hash,
+  V: Hash,
+  P: VisitPolicy<VId>,
+{
+  let vids = g.all_vids();
+  let indices = vids
+    .iter()
+    .enumerate()
+    .map(|(idx, &vid)| (vid, idx))
+    .collect::<HashMap<_, _>>();
+  let (from, to) = match (indices.get(from), indices.get(to)) {
+    (Some(&from), Some(&to)) => (from, to),
+    _ => return 0,
+  };
+
+  let mut counter = Counter {
+    g,
+    visits: vec![0; vids.len()],
+    vids,
+    indices,
+    to,
+    policy,
+    exceptions_used: 0,
+    memo: HashMap::new(),
+  };
+
+  if counter.enter(from).is_some() {
+    counter.count(from)
+  } else {
+    0
+  }
+}
+
+/// How many times each vertex of the current path was visited, and how many exceptions were used.
+struct Visits<'a, VId> {
+  counts: HashMap<&'a VId, usize>,
+  // for each vertex of the path, whether entering it used an exception
+  exceptions: Vec<bool>,
+}
+
+impl<'a, VId> Default for Visits<'a, VId> {
+  fn default() -> Self {
+    Visits {
+      counts: HashMap::new(),
+      exceptions: vec![],
+    }
+  }
+}
+
+impl<'a, VId> Visits<'a, VId>
+where
+  VId: Eq + Hash,
+{
+  fn enter<P: VisitPolicy<VId>>(&mut self, vid: &'a VId, policy: &P) -> bool {
+    let count = self.counts.get(vid).copied().unwrap_or(0);
+    let used_exception = match policy.limit(vid) {
+      Some(limit) if count >= limit => {
+        let exceptions_used = self.exceptions.iter().filter(|&&used| used).count();
+        if exceptions_used < policy.exceptions() && policy.allows_exception(vid) {
+          true
+        } else {
+          return false;
+        }
+      }
+      _ => false,
+    };
+
+    self.counts.insert(vid, count + 1);
+    self.exceptions.push(used_exception);
+    true
+  }
+
+  fn leave(&mut self, vid: &'a VId) {
+    if let Some(count) = self.counts.get_mut(vid) {
+      *count -= 1;
+    }
+    self.exceptions.pop();
+  }
+}
+
+/// State of `count_paths`, with vertices referred to by their indices in `vids`.
+struct Counter<'a, VId, E, V, P> {
+  g: &'a Graph<VId, E, V>,
+  vids: Vec<&'a VId>,
+  indices: HashMap<&'a VId, usize>,
+  to: usize,
+  policy: P,
+  // visits of each vertex on the current path; unlimited vertices are not counted,
+  // since they don't affect which paths can continue the current one
+  visits: Vec<usize>,
+  exceptions_used: usize,
+  memo: HashMap<(usize, Vec<usize>, usize), usize>,
+}
+
+impl<'a, VId, E, V, P> Counter<'a, VId, E, V, P>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  P: VisitPolicy<VId>,
+{
+  /// Counts the paths continuing the current one, which ends at `curr`.
+  fn count(&mut self, curr: usize) -> usize {
+    if curr == self.to {
+      return 1;
+    }
+
+    let key = (curr, self.visits.clone(), self.exceptions_used);
+    if let Some(&count) = self.memo.get(&key) {
+      return count;
+    }
+
+    let mut count = 0;
+    for (next, _edge) in self.g.incident_edges(self.vids[curr]).into_iter().flatten() {
+      let next = self.indices[next];
+      if let Some(used_exception) = self.enter(next) {
+        count += self.count(next);
+        self.leave(next, used_exception);
+      }
+    }
+
+    self.memo.insert(key, count);
+    count
+  }
+
+  /// Visits vertex `idx`, if the policy allows it. Returns whether an exception was used.
+  fn enter(&mut self, idx: usize) -> Option<bool> {
+    let vid = self.vids[idx];
+    let limit = match self.policy.limit(vid) {
+      None => return Some(false),
+      Some(limit) => limit,
+    };
+
+    let used_exception = self.visits[idx] >= limit;
+    if used_exception {
+      if self.exceptions_used >= self.policy.exceptions() || !self.policy.allows_exception(vid) {
+        return None;
+      }
+      self.exceptions_used += 1;
+    }
+
+    self.visits[idx] += 1;
+    Some(used_exception)
+  }
+
+  fn leave(&mut self, idx: usize, used_exception: bool) {
+    if self.policy.limit(self.vids[idx]).is_some() {
+      self.visits[idx] -= 1;
+    }
+    if used_exception {
+      self.exceptions_used -= 1;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  /// Diamond A -> B, C -> D, with a cycle B -> A, and a parallel edge C -> D.
+  fn diamond() -> Graph<&'static str, u32> {
+    let mut g = Graph::new();
+    for vid in ["A", "B", "C", "D"] {
+      g.push_vid(vid);
+    }
+    for (from, to, edge) in [
+      ("A", "B", 0),
+      ("A", "C", 1),
+      ("B", "D", 2),
+      ("C", "D", 3),
+      ("C", "D", 4),
+      ("B", "A", 5),
+    ] {
+      g.push_edge(from, to, edge);
+    }
+
+    g
+  }
+
+  #[test]
+  fn simple_paths_test() {
+    let g = diamond();
+
+    let paths = all_paths(&g, &"A", &"D", Simple).collect::<Vec<_>>();
+    assert_eq!(
+      paths.iter().map(|path| path.vertices()).collect::<Vec<_>>(),
+      vec![[&"A", &"B", &"D"], [&"A", &"C", &"D"], [&"A", &"C", &"D"]]
+    );
+    // the parallel edges make the last two paths different
+    assert_eq!(paths[1].edges(), [&1, &3]);
+    assert_eq!(paths[2].edges(), [&1, &4]);
+    assert_eq!(paths[2].cost(), 2);
+    assert_eq!(count_paths(&g, &"A", &"D", Simple), 3);
+
+    assert_eq!(
+      all_paths(&g, &"A", &"A", Simple).collect::<Vec<_>>(),
+      vec![Path::new(&"A")]
+    );
+    assert_eq!(count_paths(&g, &"A", &"A", Simple), 1);
+    assert_eq!(all_paths(&g, &"D", &"A", Simple).count(), 0);
+    assert_eq!(count_paths(&g, &"D", &"Z", Simple), 0);
+
+    // A can be visited twice, so the cycle can be taken once
+    let twice_a = |vid: &&str| if *vid == "A" { Some(2) } else { Some(1) };
+    let paths = all_paths(&g, &"A", &"D", twice_a)
+      .map(|path| path.into_vertices())
+      .collect::<HashSet<_>>();
+    assert!(paths.contains(&vec![&"A", &"B", &"A", &"C", &"D"]));
+    assert_eq!(count_paths(&g, &"A", &"D", twice_a), 5);
+    assert_eq!(all_paths(&g, &"A", &"D", twice_a).count(), 5);
+  }
+}
@@ -0,0 +1,281 @@
+//! Transitive closure and reduction: the graphs with the same reachability as the original one,
+//! with the most and the fewest edges respectively.
+//!
+//! Reachability is stored as one bitset per strongly connected component, which all of its vertices
+//! share, so merging the vertices reachable from a successor takes a pass over `n / 64` words instead
+//! of `n` hash set insertions.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::components::{index_components, index_successors};
+use crate::topo::{toposort, Cycle};
+use crate::Graph;
+
+/// Answers repeated "is `to` reachable from `from`" queries in constant time, after precomputing
+/// the vertices reachable from each vertex. Takes up to `n^2 / 8` bytes for a graph with `n` vertices.
+#[derive(Debug, Clone)]
+pub struct Reachability<'a, VId> {
+  vids: Vec<&'a VId>,
+  indices: HashMap<&'a VId, usize>,
+  /// The strongly connected component of each vertex.
+  components: Vec<usize>,
+  /// The vertices reachable from each component's vertices by a path of at least one edge.
+  rows: Vec<BitSet>,
+}
+
+impl<'a, VId> Reachability<'a, VId>
+where
+  VId: Eq + Hash,
+{
+  /// Computes the reachability of all vertices of `g`, which can have cycles.
+  ///
+  /// The vertices of a strongly connected component reach the same vertices, so the components are
+  /// processed in a single pass over the condensation, with each one merging the rows of its successors.
+  pub fn new<E, V: Hash>(g: &'a Graph<VId, E, V>) -> Self {
+    let vids = g.all_vids();
+    let indices = vids
+      .iter()
+      .enumerate()
+      .map(|(idx, &vid)| (vid, idx))
+      .collect::<HashMap<_, _>>();
+    let successors = index_successors(g, &vids);
+    let (components, count) = index_components(&successors, 0);
+
+    let mut members = vec![vec![]; count];
+    for (idx, &component) in components.iter().enumerate() {
+      members[component].push(idx);
+    }
+
+    // edges only go to the components with higher numbers, so they are done by then
+    let mut rows = vec![BitSet::new(vids.len()); count];
+    for component in (0..count).rev() {
+      let (row, done) = rows[component..].split_first_mut().unwrap();
+      let members = &members[component];
+      // a single vertex only reaches itself through a self-loop
+      let is_cycle = members.len() > 1 || successors[members[0]].contains(&members[0]);
+      for &idx in members {
+        if is_cycle {
+          row.insert(idx);
+        }
+        for &next in &successors[idx] {
+          if components[next] != component {
+            row.insert(next);
+            row.union_with(&done[components[next] - component - 1]);
+          }
+        }
+      }
+    }
+
+    Reachability {
+      vids,
+      indices,
+      components,
+      rows,
+    }
+  }
+
+  /// The vertices reachable from the vertex with index `idx` by a path of at least one edge.
+  fn row(&self, idx: usize) -> &BitSet {
+    &self.rows[self.components[idx]]
+  }
+
+  /// Tells if there is a path from `from` to `to`. A vertex is always reachable from itself,
+  /// as with a search starting at it.
+  pub fn reachable(&self, from: &VId, to: &VId) -> bool {
+    match (self.indices.get(from), self.indices.get(to)) {
+      (Some(&from), Some(&to)) => from == to || self.row(from).contains(to),
+      _ => false,
+    }
+  }
+
+  /// The vertices reachable from `from` by a path of at least one edge. Includes `from` only if it's
+  /// on a cycle.
+  pub fn reachable_from(&self, from: &VId) -> Vec<&'a VId> {
+    match self.indices.get(from) {
+      Some(&from) => self.row(from).iter().map(|idx| self.vids[idx]).collect(),
+      None => vec![],
+    }
+  }
+}
+
+/// Returns a graph with the same vertices as `g`, and an edge from each vertex to every vertex
+/// reachable from it by a path of at least one edge.
+///
+/// The new graph references vertex ids and vertices owned by `g`.
+pub fn transitive_closure<VId, E, V>(g: &Graph<VId, E, V>) -> Graph<&VId, (), &V>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let reachability = Reachability::new(g);
+
+  let mut closure = Graph::new();
+  for (vid, vertex) in g.iter_vertices() {
+    closure.push_vertex(vid, vertex);
+  }
+  for &vid in &reachability.vids {
+    for next in reachability.reachable_from(vid) {
+      closure.push_edge(vid, next, ());
+    }
+  }
+
+  closure
+}
+
+/// Returns a graph with the same vertices and reachability as the directed acyclic graph `g`,
+/// but with as few edges as possible: the edges implied by other paths are removed. E.g., for
+/// a dependency diagram, only the direct dependencies are kept.
+///
+/// The remaining edges are the original ones, and parallel edges are reduced to the first of them.
+/// The new graph references vertex ids, vertices & edges owned by `g`.
+pub fn transitive_reduction<VId, E, V>(g: &Graph<VId, E, V>) -> Result<Graph<&VId, &E, &V>, Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  // with cycles, there can be several minimal graphs, and they aren't subgraphs of `g`
+  toposort(g)?;
+  let reachability = Reachability::new(g);
+
+  let mut reduction = Graph::new();
+  for (vid, vertex) in g.iter_vertices() {
+    reduction.push_vertex(vid, vertex);
+  }
+  for (vid, edges) in g.iter_edges() {
+    // an edge to a vertex reachable from another successor is implied by the path through it
+    let mut implied = BitSet::new(reachability.vids.len());
+    for (next, _edge) in edges {
+      implied.union_with(reachability.row(reachability.indices[next]));
+    }
+
+    let mut kept = BitSet::new(reachability.vids.len());
+    for (next, edge) in edges {
+      let next_idx = reachability.indices[next];
+      if !implied.contains(next_idx) && kept.insert(next_idx) {
+        reduction.push_edge(vid, next, edge);
+      }
+    }
+  }
+
+  Ok(reduction)
+}
+
+/// A fixed-size set of indices, stored as bits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitSet {
+  words: Vec<u64>,
+}
+
+impl BitSet {
+  fn new(size: usize) -> Self {
+    BitSet {
+      words: vec![0; size.div_ceil(64)],
+    }
+  }
+
+  fn contains(&self, idx: usize) -> bool {
+    self.words[idx / 64] & (1 << (idx % 64)) != 0
+  }
+
+  /// Adds `idx`, and tells if it wasn't in the set yet.
+  fn insert(&mut self, idx: usize) -> bool {
+    let added = !self.contains(idx);
+    self.words[idx / 64] |= 1 << (idx % 64);
+    added
+  }
+
+  /// Adds all indices of `other`.
+  fn union_with(&mut self, other: &BitSet) {
+    for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+      *word |= other_word;
+    }
+  }
+
+  fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+    self.words.iter().enumerate().flat_map(|(word_idx, &word)| {
+      (0..64)
+        .filter(move |bit| word & (1 << bit) != 0)
+        .map(move |bit| word_idx * 64 + bit)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::distances_from;
+  use std::collections::HashSet;
+
+  #[test]
+  fn reachability_test() {
+    // a chain of 100 vertices going back to 50, with a branch 10 -> 200 -> 201
+    let mut g: Graph<u32> = Graph::new();
+    for vid in 0..99 {
+      g.push_edge(vid, vid + 1, ());
+    }
+    g.push_edge(99, 50, ());
+    g.push_edge(10, 200, ());
+    g.push_edge(200, 201, ());
+    g.push_vid(300);
+
+    let reachability = Reachability::new(&g);
+    for from in g.all_vids() {
+      let expected = distances_from(&g, from);
+      for to in g.all_vids() {
+        assert_eq!(
+          reachability.reachable(from, to),
+          expected.contains_key(to),
+          "{} -> {}",
+          from,
+          to
+        );
+      }
+    }
+    assert!(reachability.reachable_from(&60).contains(&&60));
+    assert!(!reachability.reachable_from(&20).contains(&&20));
+    assert!(!reachability.reachable(&0, &1000));
+
+    // a single vertex is on a cycle only with a self-loop
+    g.push_edge(201, 201, ());
+    let reachability = Reachability::new(&g);
+    assert_eq!(reachability.reachable_from(&201), [&201]);
+    assert!(!reachability.reachable_from(&200).contains(&&200));
+
+    let closure = transitive_closure(&g);
+    assert_eq!(closure.adjacent(&&200), [&&201]);
+    assert_eq!(closure.adjacent(&&0).len(), 101);
+    assert_eq!(closure.adjacent(&&99).len(), 50);
+  }
+
+  #[test]
+  fn transitive_reduction_test() {
+    let mut g: Graph<&str, u32> = Graph::new();
+    for (idx, (from, to)) in [
+      ("A", "B"),
+      ("B", "C"),
+      ("A", "C"),
+      ("C", "D"),
+      ("A", "D"),
+      ("B", "E"),
+      ("B", "E"),
+    ]
+    .iter()
+    .enumerate()
+    {
+      g.push_edge(*from, *to, idx as u32);
+    }
+
+    let reduction = transitive_reduction(&g).unwrap();
+    let edges = reduction
+      .iter_complete_edges()
+      .map(|(&&from, &&to, &&edge)| (from, to, edge))
+      .collect::<HashSet<_>>();
+    assert_eq!(
+      edges,
+      HashSet::from([("A", "B", 0), ("B", "C", 1), ("C", "D", 3), ("B", "E", 5)])
+    );
+
+    g.push_edge("D", "B", 7);
+    assert_eq!(transitive_reduction(&g).unwrap_err().vertices.len(), 3);
+  }
+}
@@ -0,0 +1,308 @@
+//! Strongly connected components of a directed graph: the maximal sets of vertices where each
+//! vertex is reachable from every other one.
+//!
+//! Contracting each component into a single vertex gives the condensation of the graph, which is
+//! always acyclic, so it can be sorted topologically even when the original graph can't.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// Identifies a strongly connected component.
+///
+/// Components are numbered in a topological order of the condensation: every edge between two
+/// components goes from a lower id to a higher one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ComponentId(pub usize);
+
+/// Finds the strongly connected components of `g` with Tarjan's algorithm, in a single depth-first
+/// search. Returns the component of each vertex.
+pub fn tarjan_scc<VId, E, V>(g: &Graph<VId, E, V>) -> HashMap<&VId, ComponentId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let successors = index_successors(g, &vids);
+  let (components, _) = index_components(&successors, 0);
+
+  vids
+    .into_iter()
+    .zip(components)
+    .map(|(vid, component)| (vid, ComponentId(component)))
+    .collect()
+}
+
+/// Finds the strongly connected components of `g` with Kosaraju's algorithm: a depth-first search
+/// finds the order of the vertices, and then a search over the reversed edges in that order
+/// finds the components one by one. Returns the component of each vertex.
+///
+/// Simpler than `tarjan_scc`, but makes two passes over the graph instead of one.
+pub fn kosaraju_scc<VId, E, V>(g: &Graph<VId, E, V>) -> HashMap<&VId, ComponentId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let successors = index_successors(g, &vids);
+  let mut predecessors = vec![vec![]; vids.len()];
+  for (idx, nexts) in successors.iter().enumerate() {
+    for &next in nexts {
+      predecessors[next].push(idx);
+    }
+  }
+
+  // the vertex finished last can't be reached from any other component, so its component is
+  // a source of the condensation, and what it reaches over the reversed edges is exactly it
+  let mut components = vec![None; vids.len()];
+  let mut found = 0;
+  for root in postorder(&successors).into_iter().rev() {
+    if components[root].is_some() {
+      continue;
+    }
+
+    components[root] = Some(ComponentId(found));
+    let mut stack = vec![root];
+    while let Some(curr) = stack.pop() {
+      for &prev in &predecessors[curr] {
+        if components[prev].is_none() {
+          components[prev] = Some(ComponentId(found));
+          stack.push(prev);
+        }
+      }
+    }
+    found += 1;
+  }
+
+  vids.into_iter().zip(components.into_iter().flatten()).collect()
+}
+
+/// Returns the condensation of `g`: a directed acyclic graph with a vertex for each strongly connected
+/// component of `g`, holding the ids of its members, and an edge between two components if there is
+/// an edge between their members. Parallel edges are merged, and edges within a component are dropped.
+pub fn condensation<VId, E, V>(g: &Graph<VId, E, V>) -> Graph<ComponentId, (), Vec<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let components = tarjan_scc(g);
+
+  let mut members: HashMap<ComponentId, Vec<&VId>> = HashMap::new();
+  for vid in g.all_vids() {
+    members.entry(components[vid]).or_default().push(vid);
+  }
+  let mut condensation = Graph::new();
+  for (component, members) in members {
+    condensation.push_vertex(component, members);
+  }
+
+  let mut seen = HashSet::new();
+  for (from, to, _edge) in g.iter_complete_edges() {
+    let (from, to) = (components[from], components[to]);
+    if from != to && seen.insert((from, to)) {
+      condensation.push_edge(from, to, ());
+    }
+  }
+
+  condensation
+}
+
+/// The distinct successors of each vertex, by the indices of `vids`.
+pub(crate) fn index_successors<VId, E, V>(g: &Graph<VId, E, V>, vids: &[&VId]) -> Vec<Vec<usize>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let indices = vids
+    .iter()
+    .enumerate()
+    .map(|(idx, &vid)| (vid, idx))
+    .collect::<HashMap<_, _>>();
+
+  vids
+    .iter()
+    .map(|&vid| {
+      let mut seen = HashSet::new();
+      g.adjacent(vid)
+        .into_iter()
+        .map(|next| indices[next])
+        .filter(|&next| seen.insert(next))
+        .collect()
+    })
+    .collect()
+}
+
+/// Tarjan's algorithm over the vertices' indices, in the subgraph of the vertices with indices from `from` on:
+/// returns the component of each vertex, numbered in a topological order of the condensation, and the number
+/// of components. The vertices before `from` are ignored, and their components are meaningless.
+pub(crate) fn index_components(successors: &[Vec<usize>], from: usize) -> (Vec<usize>, usize) {
+  // the order in which the search discovered each vertex, and the earliest discovered vertex
+  // on the stack reachable from it
+  let mut discovered = vec![None; successors.len()];
+  let mut lowlinks = vec![0; successors.len()];
+  let mut stack = vec![];
+  let mut on_stack = vec![false; successors.len()];
+  let mut next_discovered = 0;
+  // components are found sinks first, so they are numbered from the end
+  let mut components = vec![0; successors.len()];
+  let mut found = 0;
+
+  for root in from..successors.len() {
+    if discovered[root].is_some() {
+      continue;
+    }
+
+    let mut path = vec![(root, 0)];
+    discovered[root] = Some(next_discovered);
+    lowlinks[root] = next_discovered;
+    next_discovered += 1;
+    stack.push(root);
+    on_stack[root] = true;
+
+    while let Some((curr, next_idx)) = path.last_mut() {
+      let curr = *curr;
+      match successors[curr].get(*next_idx) {
+        Some(&next) if next < from => *next_idx += 1,
+        Some(&next) => {
+          *next_idx += 1;
+          match discovered[next] {
+            None => {
+              discovered[next] = Some(next_discovered);
+              lowlinks[next] = next_discovered;
+              next_discovered += 1;
+              stack.push(next);
+              on_stack[next] = true;
+              path.push((next, 0));
+            }
+            Some(next_discovered) if on_stack[next] => lowlinks[curr] = lowlinks[curr].min(next_discovered),
+            Some(_) => (),
+          }
+        }
+        None => {
+          path.pop();
+          if let Some(&(parent, _)) = path.last() {
+            lowlinks[parent] = lowlinks[parent].min(lowlinks[curr]);
+          }
+
+          // `curr` is the first discovered vertex of its component, which is on the stack above it
+          if discovered[curr] == Some(lowlinks[curr]) {
+            loop {
+              let member = stack.pop().unwrap();
+              on_stack[member] = false;
+              components[member] = found;
+              if member == curr {
+                break;
+              }
+            }
+            found += 1;
+          }
+        }
+      }
+    }
+  }
+
+  for component in &mut components[from..] {
+    *component = found - 1 - *component;
+  }
+  (components, found)
+}
+
+/// Vertex indices in the order a depth-first search finishes them, covering all vertices.
+fn postorder(successors: &[Vec<usize>]) -> Vec<usize> {
+  let mut visited = vec![false; successors.len()];
+  let mut order = Vec::with_capacity(successors.len());
+
+  for root in 0..successors.len() {
+    if visited[root] {
+      continue;
+    }
+    visited[root] = true;
+
+    // the current path, with the index of the next successor to follow from each vertex
+    let mut path = vec![(root, 0)];
+    while let Some((curr, next_idx)) = path.last_mut() {
+      let curr = *curr;
+      match successors[curr].get(*next_idx) {
+        None => {
+          path.pop();
+          order.push(curr);
+        }
+        Some(&next) => {
+          *next_idx += 1;
+          if !visited[next] {
+            visited[next] = true;
+            path.push((next, 0));
+          }
+        }
+      }
+    }
+  }
+
+  order
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::topo::toposort;
+
+  /// Groups the vertices by their components.
+  fn partition(components: &HashMap<&u32, ComponentId>) -> HashSet<Vec<u32>> {
+    let mut members: HashMap<ComponentId, Vec<u32>> = HashMap::new();
+    for (&&vid, &component) in components {
+      members.entry(component).or_default().push(vid);
+    }
+    members
+      .into_values()
+      .map(|mut members| {
+        members.sort_unstable();
+        members
+      })
+      .collect()
+  }
+
+  /// Two cycles 1 -> 2 -> 3 -> 1 and 4 <-> 5, a self-loop at 6, and 7 on its own.
+  fn graph() -> Graph<u32> {
+    let mut g = Graph::new();
+    for (from, to) in [(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 4), (5, 6), (6, 6), (2, 6)] {
+      g.push_edge(from, to, ());
+    }
+    g.push_vid(7);
+    g
+  }
+
+  #[test]
+  fn scc_test() {
+    let g = graph();
+    let expected = HashSet::from([vec![1, 2, 3], vec![4, 5], vec![6], vec![7]]);
+
+    for components in [tarjan_scc(&g), kosaraju_scc(&g)] {
+      assert_eq!(partition(&components), expected);
+      assert!(g
+        .iter_complete_edges()
+        .all(|(from, to, _)| components[from] <= components[to]));
+      assert!(components.values().all(|component| component.0 < 4));
+    }
+  }
+
+  #[test]
+  fn condensation_test() {
+    let g = graph();
+    let condensation = condensation(&g);
+
+    assert_eq!(condensation.iter_vertices().count(), 4);
+    assert_eq!(condensation.iter_complete_edges().count(), 3);
+    assert_eq!(toposort(&condensation).unwrap().len(), 4);
+
+    let components = tarjan_scc(&g);
+    let members = condensation.get_vertex(&components[&1]).unwrap();
+    assert_eq!(members.iter().collect::<HashSet<_>>(), HashSet::from([&&1, &&2, &&3]));
+    assert_eq!(
+      condensation
+        .adjacent(&components[&1])
+        .into_iter()
+        .collect::<HashSet<_>>(),
+      HashSet::from([&components[&4], &components[&6]])
+    );
+  }
+}
@@ -0,0 +1,429 @@
+//! Finding and listing the cycles of a graph.
+//!
+//! For directed graphs: `has_cycle` and `find_cycle` to check for cycles, `elementary_cycles`
+//! to list all of them, and `girth` for the length of the shortest one. For undirected graphs,
+//! where each edge is stored in both directions, `cycle_basis` and `undirected_girth`.
+//!
+//! All cycles are returned as `topo::Cycle`s, i.e. lists of vertex ids in the order of the edges,
+//! with the edge from the last vertex back to the first one closing the cycle.
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+
+use crate::components::{index_components, index_successors};
+use crate::topo::{toposort_dfs, Cycle};
+use crate::Graph;
+
+/// Tells if the directed graph `g` has a cycle.
+pub fn has_cycle<VId, E, V>(g: &Graph<VId, E, V>) -> bool
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  find_cycle(g).is_some()
+}
+
+/// Finds a cycle of the directed graph `g`, if there is one.
+pub fn find_cycle<VId, E, V>(g: &Graph<VId, E, V>) -> Option<Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  toposort_dfs(g).err()
+}
+
+/// Lists all elementary cycles of the directed graph `g`, i.e. the ones that don't repeat vertices,
+/// using Johnson's algorithm. Self-loops are cycles of a single vertex, and parallel edges don't
+/// produce separate cycles.
+///
+/// Each cycle starts at its vertex that comes first in `g`'s vertex order. Takes `O((n + e)(c + 1))`
+/// time for `c` cycles, but note that even small graphs can have exponentially many of them.
+pub fn elementary_cycles<VId, E, V>(g: &Graph<VId, E, V>) -> Vec<Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let successors = index_successors(g, &vids);
+
+  let mut cycles = vec![];
+  let mut start = 0;
+  while start < vids.len() {
+    // the cycles through the vertices from `start` on stay in the strongly connected components
+    // of their subgraph, and the next cycles start at the least vertex of a component with a cycle:
+    // one with several vertices, or a self-loop
+    let (components, _) = index_components(&successors, start);
+    let in_component = |idx: usize, next: usize| next >= start && components[next] == components[idx];
+    let least = match (start..vids.len()).find(|&idx| successors[idx].iter().any(|&next| in_component(idx, next))) {
+      Some(least) => least,
+      None => break,
+    };
+
+    let component_successors = (0..vids.len())
+      .map(|idx| {
+        if idx >= least && components[idx] == components[least] {
+          successors[idx]
+            .iter()
+            .copied()
+            .filter(|&next| in_component(idx, next))
+            .collect()
+        } else {
+          vec![]
+        }
+      })
+      .collect::<Vec<Vec<_>>>();
+    for cycle in Circuits::new(&component_successors, least).find() {
+      cycles.push(Cycle {
+        vertices: cycle.into_iter().map(|idx| vids[idx]).collect(),
+      });
+    }
+
+    start = least + 1;
+  }
+
+  cycles
+}
+
+/// Finds a cycle basis of the undirected graph `g`, which has each edge in both directions: a set
+/// of cycles from which all other cycles can be composed by taking the symmetric difference of their
+/// edges. These are the fundamental cycles of a spanning forest, each closed by a single non-tree edge.
+///
+/// There are `e - n + c` of them, for `e` undirected edges, `n` vertices and `c` connected components.
+/// Parallel edges are treated as a single edge, but self-loops are cycles of a single vertex.
+pub fn cycle_basis<VId, E, V>(g: &Graph<VId, E, V>) -> Vec<Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let successors = index_successors(g, &vids);
+  let forest = SpanningForest::new(&successors);
+
+  let mut cycles = vec![];
+  for (idx, nexts) in successors.iter().enumerate() {
+    for &next in nexts {
+      if next == idx {
+        cycles.push(Cycle {
+          vertices: vec![vids[idx]],
+        });
+      } else if idx < next && forest.parents[idx] != Some(next) && forest.parents[next] != Some(idx) {
+        let vertices = forest.tree_path(idx, next).into_iter().map(|idx| vids[idx]).collect();
+        cycles.push(Cycle { vertices });
+      }
+    }
+  }
+
+  cycles
+}
+
+/// The length of the shortest cycle of the directed graph `g`, in edges, or `None` if it's acyclic.
+/// Runs a BFS from each vertex, looking for the shortest way back to it.
+pub fn girth<VId, E, V>(g: &Graph<VId, E, V>) -> Option<usize>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let successors = index_successors(g, &vids);
+
+  let mut girth: Option<usize> = None;
+  for start in 0..vids.len() {
+    let mut distances = vec![None; vids.len()];
+    distances[start] = Some(0);
+    let mut queue = VecDeque::from([start]);
+    'bfs: while let Some(curr) = queue.pop_front() {
+      let distance = distances[curr].unwrap();
+      if matches!(girth, Some(girth) if distance + 1 >= girth) {
+        break;
+      }
+
+      for &next in &successors[curr] {
+        if next == start {
+          girth = Some(distance + 1);
+          break 'bfs;
+        }
+        if distances[next].is_none() {
+          distances[next] = Some(distance + 1);
+          queue.push_back(next);
+        }
+      }
+    }
+  }
+
+  girth
+}
+
+/// The length of the shortest cycle of the undirected graph `g`, which has each edge in both directions,
+/// or `None` if it's a forest. Going back and forth along the same edge isn't a cycle.
+pub fn undirected_girth<VId, E, V>(g: &Graph<VId, E, V>) -> Option<usize>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let successors = index_successors(g, &vids);
+
+  let mut girth: Option<usize> = None;
+  let mut record = |length: usize| girth = Some(girth.map_or(length, |girth| girth.min(length)));
+  for start in 0..vids.len() {
+    // distances from `start`, and the vertices they were reached from
+    let mut reached = vec![None; vids.len()];
+    reached[start] = Some((0, start));
+    let mut queue = VecDeque::from([start]);
+    while let Some(curr) = queue.pop_front() {
+      let (distance, parent) = reached[curr].unwrap();
+      for &next in &successors[curr] {
+        match reached[next] {
+          _ if next == curr => record(1),
+          None => {
+            reached[next] = Some((distance + 1, curr));
+            queue.push_back(next);
+          }
+          // the tree edge back to the parent
+          Some(_) if next == parent => (),
+          Some((next_distance, _)) if next_distance >= distance => record(distance + next_distance + 1),
+          Some(_) => (),
+        }
+      }
+    }
+  }
+
+  girth
+}
+
+/// The state of Johnson's search for the cycles through a single start vertex.
+struct Circuits<'s> {
+  successors: &'s [Vec<usize>],
+  start: usize,
+  /// The vertices which can't lead back to the start without going through the current path.
+  blocked: Vec<bool>,
+  /// The vertices to unblock once each vertex gets unblocked.
+  blocked_by: Vec<HashSet<usize>>,
+}
+
+impl<'s> Circuits<'s> {
+  fn new(successors: &'s [Vec<usize>], start: usize) -> Self {
+    Circuits {
+      successors,
+      start,
+      blocked: vec![false; successors.len()],
+      blocked_by: vec![HashSet::new(); successors.len()],
+    }
+  }
+
+  /// Finds all cycles through the start, using the successors as the graph.
+  fn find(mut self) -> Vec<Vec<usize>> {
+    let mut cycles = vec![];
+    self.blocked[self.start] = true;
+
+    // the current path, with the index of the next successor to follow from each vertex,
+    // and whether a cycle was found through it
+    let mut path = vec![(self.start, 0, false)];
+    while let Some((curr, next_idx, found)) = path.last_mut() {
+      let curr = *curr;
+      match self.successors[curr].get(*next_idx) {
+        Some(&next) => {
+          *next_idx += 1;
+          if next == self.start {
+            *found = true;
+            cycles.push(path.iter().map(|&(vid, _, _)| vid).collect());
+          } else if !self.blocked[next] {
+            self.blocked[next] = true;
+            path.push((next, 0, false));
+          }
+        }
+        None => {
+          let found = *found;
+          path.pop();
+          if found {
+            self.unblock(curr);
+          } else {
+            for &next in &self.successors[curr] {
+              self.blocked_by[next].insert(curr);
+            }
+          }
+          if let Some((_, _, parent_found)) = path.last_mut() {
+            *parent_found |= found;
+          }
+        }
+      }
+    }
+
+    cycles
+  }
+
+  fn unblock(&mut self, vid: usize) {
+    let mut stack = vec![vid];
+    while let Some(curr) = stack.pop() {
+      if self.blocked[curr] {
+        self.blocked[curr] = false;
+        stack.extend(self.blocked_by[curr].drain());
+      }
+    }
+  }
+}
+
+/// A BFS spanning forest of an undirected graph, given by the indices of the vertices.
+struct SpanningForest {
+  parents: Vec<Option<usize>>,
+  depths: Vec<usize>,
+}
+
+impl SpanningForest {
+  fn new(successors: &[Vec<usize>]) -> Self {
+    let mut parents = vec![None; successors.len()];
+    let mut depths = vec![0; successors.len()];
+    let mut visited = vec![false; successors.len()];
+
+    for root in 0..successors.len() {
+      if visited[root] {
+        continue;
+      }
+      visited[root] = true;
+
+      let mut queue = VecDeque::from([root]);
+      while let Some(curr) = queue.pop_front() {
+        for &next in &successors[curr] {
+          if !visited[next] {
+            visited[next] = true;
+            parents[next] = Some(curr);
+            depths[next] = depths[curr] + 1;
+            queue.push_back(next);
+          }
+        }
+      }
+    }
+
+    SpanningForest { parents, depths }
+  }
+
+  /// The path from `a` to `b` along the tree: up to their lowest common ancestor, and down again.
+  fn tree_path(&self, mut a: usize, mut b: usize) -> Vec<usize> {
+    let mut up = vec![];
+    let mut down = vec![];
+    while self.depths[a] > self.depths[b] {
+      up.push(a);
+      a = self.parents[a].unwrap();
+    }
+    while self.depths[b] > self.depths[a] {
+      down.push(b);
+      b = self.parents[b].unwrap();
+    }
+    while a != b {
+      up.push(a);
+      down.push(b);
+      a = self.parents[a].unwrap();
+      b = self.parents[b].unwrap();
+    }
+
+    up.push(a);
+    up.extend(down.into_iter().rev());
+    up
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Tells if `cycle` is a cycle of `g`.
+  fn is_cycle<VId: Eq + Hash>(g: &Graph<VId>, cycle: &Cycle<&VId>) -> bool {
+    let vertices = &cycle.vertices;
+    let distinct = vertices.iter().collect::<HashSet<_>>();
+    distinct.len() == vertices.len()
+      && (0..vertices.len()).all(|idx| {
+        let next = vertices[(idx + 1) % vertices.len()];
+        g.adjacent(vertices[idx]).contains(&next)
+      })
+  }
+
+  #[test]
+  fn directed_cycles_test() {
+    let mut g: Graph<u32> = Graph::new();
+    for vid in 1..=5 {
+      g.push_vid(vid);
+    }
+    for (from, to) in [(1, 2), (2, 3), (3, 1), (2, 4), (4, 5), (5, 2), (3, 3), (4, 2), (4, 2)] {
+      g.push_edge(from, to, ());
+    }
+
+    let cycles = elementary_cycles(&g);
+    assert!(cycles.iter().all(|cycle| is_cycle(&g, cycle)));
+    // rotations of the same cycle only come up once
+    let distinct = cycles
+      .iter()
+      .map(|cycle| {
+        let mut vertices = cycle.vertices.clone();
+        vertices.sort();
+        vertices
+      })
+      .collect::<HashSet<_>>();
+    assert_eq!(distinct.len(), cycles.len());
+    assert_eq!(
+      distinct,
+      HashSet::from([vec![&1, &2, &3], vec![&2, &4, &5], vec![&2, &4], vec![&3]])
+    );
+
+    // a complete graph of 4 vertices, behind a chain of vertices not on any cycle
+    let mut complete: Graph<u32> = Graph::new();
+    for vid in 0..4 {
+      complete.push_edge(vid, vid + 1, ());
+    }
+    for from in 4..8 {
+      for to in 4..8 {
+        if from != to {
+          complete.push_edge(from, to, ());
+        }
+      }
+    }
+    let cycles = elementary_cycles(&complete);
+    // 6 pairs, 4 triangles going both ways, and 6 orders of all 4 vertices
+    assert_eq!(cycles.len(), 6 + 4 * 2 + 6);
+    assert!(cycles.iter().all(|cycle| is_cycle(&complete, cycle)));
+
+    assert!(has_cycle(&g));
+    assert!(is_cycle(&g, &find_cycle(&g).unwrap()));
+    assert_eq!(girth(&g), Some(1));
+
+    let mut dag: Graph<u32> = Graph::new();
+    dag.push_edge(1, 2, ());
+    dag.push_edge(1, 3, ());
+    dag.push_edge(2, 3, ());
+    assert!(!has_cycle(&dag));
+    assert!(elementary_cycles(&dag).is_empty());
+    assert_eq!(girth(&dag), None);
+    dag.push_edge(3, 1, ());
+    assert_eq!(girth(&dag), Some(2));
+  }
+
+  #[test]
+  fn undirected_cycles_test() {
+    // a 3 x 3 grid of vertices has 4 unit squares, and a triangle hangs off its corner
+    let mut g: Graph<(i32, i32)> = Graph::new();
+    for row in 0..3 {
+      for col in 0..3 {
+        if row < 2 {
+          g.push_undirected_edge((row, col), (row + 1, col), ());
+        }
+        if col < 2 {
+          g.push_undirected_edge((row, col), (row, col + 1), ());
+        }
+      }
+    }
+    assert_eq!(undirected_girth(&g), Some(4));
+
+    g.push_undirected_edge((2, 2), (3, 3), ());
+    g.push_undirected_edge((3, 3), (3, 4), ());
+    g.push_undirected_edge((3, 4), (2, 2), ());
+    g.push_undirected_edge((5, 5), (6, 6), ());
+
+    let basis = cycle_basis(&g);
+    // 16 edges, 13 vertices, 2 components
+    assert_eq!(basis.len(), 4 + 1);
+    assert!(basis.iter().all(|cycle| is_cycle(&g, cycle)));
+    assert_eq!(undirected_girth(&g), Some(3));
+
+    g.push_edge((6, 6), (6, 6), ());
+    assert_eq!(cycle_basis(&g).len(), 6);
+    assert_eq!(undirected_girth(&g), Some(1));
+  }
+}
@@ -0,0 +1,249 @@
+//! Algorithms for directed acyclic graphs, based on processing the vertices in a topological order:
+//! the longest path, and the critical path analysis of a schedule.
+//!
+//! All of them return a `topo::Cycle` error if the graph turns out to have a cycle.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Sub;
+
+use crate::search::Weight;
+use crate::topo::{toposort, Cycle};
+use crate::{Graph, Path};
+
+/// Finds the heaviest path of a directed acyclic graph, with `weight_fn` giving the weight of each edge.
+/// The path can start and end at any vertex.
+///
+/// Unlike the shortest path, the longest one is NP-hard to find in general graphs, but in a DAG it takes
+/// linear time. Returns the path with its weight as the cost, or `None` if the graph is empty.
+/// If there are several heaviest paths, returns one of them.
+pub fn longest_path_dag<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  weight_fn: F,
+) -> Result<Option<Path<&'a VId, &'a E, W>>, Cycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let order = toposort(g)?;
+  let longest = longest_paths(g, &order, weight_fn);
+  Ok(heaviest_end(&order, &longest).map(|end| trace_longest(&longest, end)))
+}
+
+/// The critical path analysis of a DAG of tasks, as computed by `critical_path`.
+///
+/// The times are measured from the start of the whole schedule, with the weights of the edges being
+/// the time it takes to get from one vertex to the next.
+#[derive(Debug, Clone)]
+pub struct CriticalPath<'a, VId, E, W> {
+  /// Maps each vertex id to the earliest time it can be reached, i.e. the weight of the longest path
+  /// ending at it.
+  pub earliest: HashMap<&'a VId, W>,
+  /// Maps each vertex id to the latest time it can be reached without delaying the end of the schedule.
+  pub latest: HashMap<&'a VId, W>,
+  /// One of the longest paths of the graph. Any delay on it delays the whole schedule.
+  pub path: Path<&'a VId, &'a E, W>,
+}
+
+impl<'a, VId, E, W> CriticalPath<'a, VId, E, W>
+where
+  VId: Eq + Hash,
+  W: Weight + Sub<Output = W>,
+{
+  /// The time the whole schedule takes, i.e. the weight of the critical path.
+  pub fn length(&self) -> W {
+    self.path.cost()
+  }
+
+  /// How much reaching `vid` can be delayed without delaying the whole schedule.
+  /// `None` if `vid` is not in the graph.
+  pub fn slack(&self, vid: &VId) -> Option<W> {
+    Some(*self.latest.get(vid)? - *self.earliest.get(vid)?)
+  }
+
+  /// Tells if `vid` can't be delayed without delaying the whole schedule.
+  pub fn is_critical(&self, vid: &VId) -> bool {
+    self.slack(vid) == Some(W::default())
+  }
+}
+
+/// Computes the earliest and latest times of reaching each vertex of a DAG of tasks, and the critical path,
+/// with `weight_fn` giving the weight of each edge, e.g. the duration of a build step.
+/// If the durations belong to the vertices, the weight of an edge can be the duration of the vertex it
+/// starts from, with an extra vertex at the end to account for the last steps.
+///
+/// Returns `None` if the graph is empty.
+pub fn critical_path<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  weight_fn: F,
+) -> Result<Option<CriticalPath<'a, VId, E, W>>, Cycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight + Sub<Output = W>,
+{
+  let order = toposort(g)?;
+  let longest = longest_paths(g, &order, &weight_fn);
+  let path = match heaviest_end(&order, &longest) {
+    Some(end) => trace_longest(&longest, end),
+    None => return Ok(None),
+  };
+
+  let length = path.cost();
+  let mut latest = HashMap::new();
+  for &vid in order.iter().rev() {
+    let mut vid_latest = length;
+    for (next, edge) in g.incident_edges(vid).into_iter().flatten() {
+      vid_latest = vid_latest.min(latest[next] - weight_fn(edge));
+    }
+    latest.insert(vid, vid_latest);
+  }
+
+  let earliest = longest.into_iter().map(|(vid, (weight, _))| (vid, weight)).collect();
+  Ok(Some(CriticalPath { earliest, latest, path }))
+}
+
+/// The weight of the longest path ending at each vertex, with the last step of it, if any.
+type Longest<'a, VId, E, W> = HashMap<&'a VId, (W, Option<(&'a VId, &'a E)>)>;
+
+/// Finds the longest path ending at each vertex, going through the vertices in the topological `order`.
+fn longest_paths<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  order: &[&'a VId],
+  weight_fn: F,
+) -> Longest<'a, VId, E, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let mut longest: Longest<VId, E, W> = order.iter().map(|&vid| (vid, (W::default(), None))).collect();
+  for &vid in order {
+    let weight = longest[vid].0;
+    for (next, edge) in g.incident_edges(vid).into_iter().flatten() {
+      let candidate = weight + weight_fn(edge);
+      let next_longest = longest.get_mut(next).unwrap();
+      if candidate > next_longest.0 {
+        *next_longest = (candidate, Some((vid, edge)));
+      }
+    }
+  }
+
+  longest
+}
+
+/// The vertex where the heaviest path ends. The first one in the `order` wins the ties.
+fn heaviest_end<'a, VId, E, W>(order: &[&'a VId], longest: &Longest<'a, VId, E, W>) -> Option<&'a VId>
+where
+  VId: Eq + Hash,
+  W: Weight,
+{
+  let mut heaviest: Option<(&VId, W)> = None;
+  for &vid in order {
+    let weight = longest[vid].0;
+    match heaviest {
+      Some((_, max)) if max >= weight => (),
+      _ => heaviest = Some((vid, weight)),
+    }
+  }
+  heaviest.map(|(vid, _)| vid)
+}
+
+/// Follows the last steps of the longest paths back from `end`.
+fn trace_longest<'a, VId, E, W>(longest: &Longest<'a, VId, E, W>, end: &'a VId) -> Path<&'a VId, &'a E, W>
+where
+  VId: Eq + Hash,
+  W: Weight,
+{
+  let mut vertices = vec![end];
+  let mut edges = vec![];
+  let mut curr = end;
+  while let (_, Some((prev, edge))) = longest[curr] {
+    vertices.push(prev);
+    edges.push(edge);
+    curr = prev;
+  }
+
+  vertices.reverse();
+  edges.reverse();
+  Path::from_parts(vertices, edges, longest[end].0)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Events of a small project, with the durations of the activities between them.
+  fn project() -> Graph<&'static str, u32> {
+    let mut g = Graph::new();
+    for (from, to, duration) in [
+      ("S", "A", 3),
+      ("S", "B", 2),
+      ("A", "C", 4),
+      ("B", "C", 1),
+      ("B", "D", 3),
+      ("C", "E", 2),
+      ("D", "E", 2),
+    ] {
+      g.push_edge(from, to, duration);
+    }
+    g
+  }
+
+  #[test]
+  fn longest_path_test() {
+    let mut g = project();
+    let path = longest_path_dag(&g, |d| *d).unwrap().unwrap();
+    assert_eq!(path.vertices(), [&"S", &"A", &"C", &"E"]);
+    assert_eq!(path.cost(), 9);
+
+    // the heavier one of the parallel edges is taken
+    g.push_edge("B", "D", 6);
+    let path = longest_path_dag(&g, |d| *d).unwrap().unwrap();
+    assert_eq!(path.vertices(), [&"S", &"B", &"D", &"E"]);
+    assert_eq!(path.edges(), [&2, &6, &2]);
+    assert_eq!(path.cost(), 10);
+
+    let empty: Graph<&str, u32> = Graph::new();
+    assert_eq!(longest_path_dag(&empty, |d| *d), Ok(None));
+
+    g.push_edge("E", "B", 1);
+    let cycle = longest_path_dag(&g, |d| *d).unwrap_err();
+    assert_eq!(cycle.vertices.len(), 3);
+  }
+
+  #[test]
+  fn critical_path_test() {
+    let g = project();
+    let analysis = critical_path(&g, |d| *d).unwrap().unwrap();
+    assert_eq!(analysis.length(), 9);
+    assert_eq!(analysis.path.vertices(), [&"S", &"A", &"C", &"E"]);
+
+    for (vid, earliest, latest) in [
+      ("S", 0, 0),
+      ("A", 3, 3),
+      ("B", 2, 4),
+      ("C", 7, 7),
+      ("D", 5, 7),
+      ("E", 9, 9),
+    ] {
+      assert_eq!(
+        (analysis.earliest[&vid], analysis.latest[&vid]),
+        (earliest, latest),
+        "{}",
+        vid
+      );
+    }
+    assert_eq!(analysis.slack(&"B"), Some(2));
+    assert_eq!(analysis.slack(&"Z"), None);
+    assert!(analysis.is_critical(&"C"));
+    assert!(!analysis.is_critical(&"D"));
+
+    let mut g = project();
+    g.push_edge("E", "S", 0);
+    assert!(critical_path(&g, |d| *d).is_err());
+  }
+}
@@ -0,0 +1,309 @@
+//! A dependency graph that keeps its vertices in a valid evaluation order while it's being edited,
+//! like the cells of a spreadsheet or the bindings of a program.
+//!
+//! The order is maintained incrementally with the Pearce–Kelly algorithm: adding a dependency
+//! only reorders the vertices between its endpoints' current positions, instead of sorting
+//! the whole graph again with `topo::toposort`.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use fnv::FnvHashMap;
+
+use crate::topo::Cycle;
+use crate::Graph;
+
+/// A directed acyclic graph of dependencies between vertices, with a topological order of them.
+///
+/// An edge goes from a dependent to its dependency, e.g. from `x` to `y` if `x = y * 2`. The order
+/// has every dependency before its dependents, so evaluating the vertices in it always finds their
+/// dependencies already evaluated.
+#[derive(Debug)]
+pub struct DependencyGraph<VId, V = ()> {
+  graph: Graph<VId, (), V>,
+  /// The reversed edges of `graph`, from a dependency to its dependents.
+  dependents: FnvHashMap<VId, Vec<VId>>,
+  order: Vec<VId>,
+  positions: HashMap<VId, usize>,
+}
+
+impl<VId, V> DependencyGraph<VId, V>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  pub fn new() -> DependencyGraph<VId, V> {
+    DependencyGraph {
+      graph: Graph::new(),
+      dependents: FnvHashMap::default(),
+      order: vec![],
+      positions: HashMap::new(),
+    }
+  }
+
+  /// Sets the data of the vertex `vid`, adding it if needed.
+  pub fn push_vertex(&mut self, vid: VId, vertex: V) {
+    self.insert(&vid);
+    self.graph.push_vertex(vid, vertex);
+  }
+
+  /// Makes `dependent` depend on `dependency`, adding them if needed, and updates the order.
+  ///
+  /// If `dependency` already depends on `dependent`, directly or not, returns the cycle the new
+  /// edge would close, in the order of the edges, and leaves the graph unchanged.
+  pub fn add_dependency(&mut self, dependent: VId, dependency: VId) -> Result<(), Cycle<VId>> {
+    if dependent == dependency {
+      return Err(Cycle {
+        vertices: vec![dependent],
+      });
+    }
+    if self.depends_on(&dependent, &dependency) {
+      return Ok(());
+    }
+    self.insert(&dependent);
+    self.insert(&dependency);
+
+    let lower = self.positions[&dependent];
+    let upper = self.positions[&dependency];
+    if upper > lower {
+      // the dependents of `dependent` that are currently placed before `dependency`
+      let forward = self.affected_dependents(&dependent, &dependency, upper)?;
+      // the dependencies of `dependency` that are currently placed after `dependent`
+      let backward = self.affected_dependencies(&dependency, lower);
+      self.reorder(backward, forward);
+    }
+
+    self.graph.push_edge(dependent.clone(), dependency.clone(), ());
+    self.dependents.entry(dependency).or_default().push(dependent);
+    Ok(())
+  }
+
+  /// Removes the dependency of `dependent` on `dependency`. Returns `false` if there was none.
+  ///
+  /// The current order stays valid, so it's not changed.
+  pub fn remove_dependency(&mut self, dependent: &VId, dependency: &VId) -> bool {
+    if self.graph.remove_edge(dependent, dependency).is_none() {
+      return false;
+    }
+
+    let dependents = self.dependents.get_mut(dependency).unwrap();
+    let idx = dependents.iter().position(|vid| vid == dependent).unwrap();
+    dependents.remove(idx);
+    if dependents.is_empty() {
+      self.dependents.remove(dependency);
+    }
+    true
+  }
+
+  /// Tells if `dependent` directly depends on `dependency`.
+  pub fn depends_on(&self, dependent: &VId, dependency: &VId) -> bool {
+    self.dependencies(dependent).contains(&dependency)
+  }
+
+  /// The vertices `vid` directly depends on.
+  pub fn dependencies(&self, vid: &VId) -> Vec<&VId> {
+    self.graph.adjacent(vid)
+  }
+
+  /// The vertices directly depending on `vid`.
+  pub fn dependents(&self, vid: &VId) -> Vec<&VId> {
+    match self.dependents.get(vid) {
+      Some(dependents) => dependents.iter().collect(),
+      None => vec![],
+    }
+  }
+
+  /// All vertices, with every dependency before its dependents.
+  pub fn order(&self) -> &[VId] {
+    &self.order
+  }
+
+  /// The vertices that need to be evaluated again once `changed` changes: `changed` itself, and
+  /// everything depending on it, directly or not. They are returned in the evaluation order, so
+  /// `changed` comes first. Empty if `changed` is not in the graph.
+  pub fn dirty(&self, changed: &VId) -> Vec<&VId> {
+    let mut dirty = match self.positions.get_key_value(changed) {
+      Some((vid, _)) => vec![vid],
+      None => return vec![],
+    };
+
+    let mut seen = HashSet::new();
+    seen.insert(dirty[0]);
+    let mut idx = 0;
+    while idx < dirty.len() {
+      for next in self.dependents(dirty[idx]) {
+        if seen.insert(next) {
+          dirty.push(next);
+        }
+      }
+      idx += 1;
+    }
+
+    dirty.sort_by_key(|&vid| self.positions[vid]);
+    dirty
+  }
+
+  /// The underlying graph, with edges from the dependents to their dependencies.
+  pub fn graph(&self) -> &Graph<VId, (), V> {
+    &self.graph
+  }
+
+  /// Adds `vid` at the end of the order, if it's not there yet.
+  fn insert(&mut self, vid: &VId) {
+    if !self.positions.contains_key(vid) {
+      self.positions.insert(vid.clone(), self.order.len());
+      self.order.push(vid.clone());
+    }
+  }
+
+  /// Finds the vertices depending on `start` placed before `upper` in the order.
+  /// If `target` is one of them, the new dependency on it would close a cycle, which is returned.
+  fn affected_dependents(&self, start: &VId, target: &VId, upper: usize) -> Result<Vec<VId>, Cycle<VId>> {
+    let mut parents = HashMap::new();
+    let mut visited = vec![start];
+    let mut stack = vec![start];
+    while let Some(curr) = stack.pop() {
+      for next in self.dependents(curr) {
+        if parents.contains_key(next) || next == start {
+          continue;
+        }
+        parents.insert(next, curr);
+
+        if next == target {
+          // the dependents lead from `start` to `target`, against the edges
+          let mut vertices = vec![target.clone()];
+          let mut vid = target;
+          while vid != start {
+            vid = parents[vid];
+            vertices.push(vid.clone());
+          }
+          return Err(Cycle { vertices });
+        }
+        if self.positions[next] < upper {
+          visited.push(next);
+          stack.push(next);
+        }
+      }
+    }
+
+    Ok(visited.into_iter().cloned().collect())
+  }
+
+  /// Finds the dependencies of `start` placed after `lower` in the order.
+  fn affected_dependencies(&self, start: &VId, lower: usize) -> Vec<VId> {
+    let mut seen = HashSet::new();
+    seen.insert(start);
+    let mut visited = vec![start];
+    let mut stack = vec![start];
+    while let Some(curr) = stack.pop() {
+      for next in self.dependencies(curr) {
+        if self.positions[next] > lower && seen.insert(next) {
+          visited.push(next);
+          stack.push(next);
+        }
+      }
+    }
+
+    visited.into_iter().cloned().collect()
+  }
+
+  /// Moves the `backward` vertices before the `forward` ones, keeping the relative order within each
+  /// group, and reusing the positions they occupied together.
+  fn reorder(&mut self, mut backward: Vec<VId>, mut forward: Vec<VId>) {
+    backward.sort_by_key(|vid| self.positions[vid]);
+    forward.sort_by_key(|vid| self.positions[vid]);
+
+    let mut positions = backward
+      .iter()
+      .chain(&forward)
+      .map(|vid| self.positions[vid])
+      .collect::<Vec<_>>();
+    positions.sort_unstable();
+
+    for (vid, position) in backward.into_iter().chain(forward).zip(positions) {
+      self.positions.insert(vid.clone(), position);
+      self.order[position] = vid;
+    }
+  }
+}
+
+impl<VId, V> Default for DependencyGraph<VId, V>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Tells if every dependency of `deps` comes before its dependents in the order.
+  fn is_ordered<VId: Eq + Hash + Clone>(deps: &DependencyGraph<VId>) -> bool {
+    let positions = deps
+      .order()
+      .iter()
+      .enumerate()
+      .map(|(idx, vid)| (vid, idx))
+      .collect::<HashMap<_, _>>();
+    deps
+      .graph()
+      .iter_complete_edges()
+      .all(|(dependent, dependency, _)| positions[dependency] < positions[dependent])
+  }
+
+  #[test]
+  fn incremental_order_test() {
+    let mut deps: DependencyGraph<u32> = DependencyGraph::new();
+    for vid in 0..8 {
+      deps.push_vertex(vid, ());
+    }
+
+    // most of the edges go against the initial order, so the vertices between their ends have to move
+    for (dependent, dependency) in [(0, 1), (1, 2), (4, 7), (2, 5), (3, 6), (6, 4), (0, 3)] {
+      assert_eq!(deps.add_dependency(dependent, dependency), Ok(()));
+      assert!(
+        is_ordered(&deps),
+        "{:?} after {} -> {}",
+        deps.order(),
+        dependent,
+        dependency
+      );
+    }
+    assert_eq!(deps.order().len(), 8);
+
+    assert_eq!(
+      deps.add_dependency(7, 0),
+      Err(Cycle {
+        vertices: vec![0, 3, 6, 4, 7]
+      })
+    );
+    assert_eq!(deps.add_dependency(5, 5), Err(Cycle { vertices: vec![5] }));
+    assert!(!deps.depends_on(&7, &0));
+    assert!(is_ordered(&deps));
+
+    // without 6 depending on 4, 4 can depend on 0
+    assert!(deps.remove_dependency(&6, &4));
+    assert!(!deps.remove_dependency(&6, &4));
+    assert_eq!(deps.add_dependency(4, 0), Ok(()));
+    assert!(is_ordered(&deps));
+  }
+
+  #[test]
+  fn dirty_test() {
+    // a = 1, b = a + 1, c = b * 2, d = a + c, e = 5
+    let mut deps: DependencyGraph<&str> = DependencyGraph::new();
+    for (dependent, dependency) in [("b", "a"), ("c", "b"), ("d", "a"), ("d", "c")] {
+      deps.add_dependency(dependent, dependency).unwrap();
+    }
+    deps.push_vertex("e", ());
+
+    assert_eq!(deps.dirty(&"a"), vec![&"a", &"b", &"c", &"d"]);
+    assert_eq!(deps.dirty(&"c"), vec![&"c", &"d"]);
+    assert_eq!(deps.dirty(&"e"), vec![&"e"]);
+    assert!(deps.dirty(&"z").is_empty());
+    assert_eq!(deps.dependents(&"a"), vec![&"b", &"d"]);
+  }
+}
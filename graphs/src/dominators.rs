@@ -0,0 +1,342 @@
+//! Dominators of a flow graph, such as the control flow graph of a function.
+//!
+//! A vertex `a` dominates a vertex `b` if every path from the root to `b` goes through `a`.
+//! The immediate dominator of `b` is its closest strict dominator, and together they form
+//! the dominator tree. Post-dominators are the same, but for the paths from a vertex to the exit.
+//!
+//! Uses the iterative algorithm by Cooper, Harvey and Kennedy, "A Simple, Fast Dominance Algorithm".
+//! It's asymptotically slower than Lengauer–Tarjan, but faster in practice on graphs with fewer than
+//! tens of thousands of vertices.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// The dominance relation of the vertices reachable from a root, as computed by `dominators`.
+#[derive(Debug, Clone)]
+pub struct Dominators<'a, VId> {
+  pub root: &'a VId,
+  /// Maps each vertex reachable from the root, except the root itself, to its immediate dominator.
+  pub idoms: HashMap<&'a VId, &'a VId>,
+  /// Maps each vertex reachable from the root to its dominance frontier: the vertices it doesn't
+  /// strictly dominate, but dominates some of their predecessors. This is where its dominance ends,
+  /// e.g. where an assignment inside it needs a phi function.
+  pub frontiers: HashMap<&'a VId, HashSet<&'a VId>>,
+}
+
+impl<'a, VId> Dominators<'a, VId>
+where
+  VId: Eq + Hash,
+{
+  /// The immediate dominator of `vid`. `None` for the root and the unreachable vertices.
+  pub fn immediate_dominator(&self, vid: &VId) -> Option<&'a VId> {
+    self.idoms.get(vid).copied()
+  }
+
+  /// All dominators of `vid`, starting with `vid` itself and ending with the root.
+  /// Empty if `vid` is not reachable from the root.
+  pub fn dominators_of(&self, vid: &VId) -> Vec<&'a VId> {
+    let mut curr = match self.frontiers.get_key_value(vid) {
+      Some((&vid, _)) => vid,
+      None => return vec![],
+    };
+
+    let mut dominators = vec![curr];
+    while let Some(&idom) = self.idoms.get(curr) {
+      dominators.push(idom);
+      curr = idom;
+    }
+    dominators
+  }
+
+  /// Tells if `a` dominates `b`. Every reachable vertex dominates itself.
+  pub fn dominates(&self, a: &VId, b: &VId) -> bool {
+    self.dominators_of(b).contains(&a)
+  }
+
+  /// The dominator tree: a graph with the vertices reachable from the root, and an edge from
+  /// each immediate dominator to the vertices it immediately dominates.
+  pub fn tree(&self) -> Graph<&'a VId> {
+    let mut tree = Graph::new();
+    for &vid in self.frontiers.keys() {
+      tree.push_vid(vid);
+    }
+    for (&vid, &idom) in &self.idoms {
+      tree.push_edge(idom, vid, ());
+    }
+    tree
+  }
+}
+
+/// Finds the dominators of the vertices of `g` reachable from `root`.
+pub fn dominators<'a, VId, E, V>(g: &'a Graph<VId, E, V>, root: &'a VId) -> Dominators<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  // vertices are referred to by their postorder indices, so the root has the highest one
+  let vids = postorder(g, root);
+  let indices = vids
+    .iter()
+    .enumerate()
+    .map(|(idx, &vid)| (vid, idx))
+    .collect::<HashMap<_, _>>();
+  let mut predecessors = vec![vec![]; vids.len()];
+  for (idx, &vid) in vids.iter().enumerate() {
+    for next in g.adjacent(vid) {
+      predecessors[indices[next]].push(idx);
+    }
+  }
+
+  let root_idx = vids.len() - 1;
+  let mut idoms = vec![None; vids.len()];
+  idoms[root_idx] = Some(root_idx);
+  let mut changed = true;
+  while changed {
+    changed = false;
+    // in reverse postorder, a vertex is processed after at least one of its predecessors
+    for idx in (0..root_idx).rev() {
+      let mut new_idom = None;
+      for &pred in &predecessors[idx] {
+        if idoms[pred].is_none() {
+          continue;
+        }
+        new_idom = match new_idom {
+          None => Some(pred),
+          Some(new_idom) => Some(intersect(&idoms, pred, new_idom)),
+        };
+      }
+
+      if new_idom != idoms[idx] {
+        idoms[idx] = new_idom;
+        changed = true;
+      }
+    }
+  }
+
+  // a vertex is in the frontiers of its predecessors' dominators, up to its own immediate dominator;
+  // the root also has a virtual entry edge, so a single edge back to it is enough
+  let mut frontiers = vec![HashSet::new(); vids.len()];
+  for (idx, preds) in predecessors.iter().enumerate() {
+    if preds.len() < 2 && idx != root_idx {
+      continue;
+    }
+    // the root's immediate dominator is the virtual entry, so the walk goes up to the root itself
+    let idom = if idx == root_idx { None } else { idoms[idx] };
+    for &pred in preds {
+      let mut runner = Some(pred);
+      while runner != idom {
+        let curr = runner.unwrap();
+        frontiers[curr].insert(vids[idx]);
+        runner = if curr == root_idx { None } else { idoms[curr] };
+      }
+    }
+  }
+
+  Dominators {
+    root,
+    idoms: (0..root_idx)
+      .map(|idx| (vids[idx], vids[idoms[idx].unwrap()]))
+      .collect(),
+    frontiers: vids.iter().copied().zip(frontiers).collect(),
+  }
+}
+
+/// Finds the post-dominators of the vertices of `g` from which `exit` is reachable: `a` post-dominates
+/// `b` if every path from `b` to `exit` goes through `a`. The frontiers are the post-dominance
+/// frontiers, which give the control dependencies of a control flow graph.
+///
+/// These are the dominators of the graph with the edges reversed, and `exit` as the root.
+pub fn post_dominators<'a, VId, E, V>(g: &'a Graph<VId, E, V>, exit: &'a VId) -> Dominators<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let reversed = g.reversed();
+  let dominators = dominators(&reversed, &exit);
+
+  Dominators {
+    root: exit,
+    idoms: dominators.idoms.into_iter().map(|(&vid, &idom)| (vid, idom)).collect(),
+    frontiers: dominators
+      .frontiers
+      .into_iter()
+      .map(|(&vid, frontier)| (vid, frontier.into_iter().copied().collect()))
+      .collect(),
+  }
+}
+
+/// The closest common dominator of `a` and `b`, found by walking up the dominator tree
+/// from the one with the lower postorder index.
+fn intersect(idoms: &[Option<usize>], mut a: usize, mut b: usize) -> usize {
+  while a != b {
+    while a < b {
+      a = idoms[a].unwrap();
+    }
+    while b < a {
+      b = idoms[b].unwrap();
+    }
+  }
+  a
+}
+
+/// The vertices reachable from `root`, in the order a depth-first search finishes them.
+fn postorder<'a, VId, E, V>(g: &'a Graph<VId, E, V>, root: &'a VId) -> Vec<&'a VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let mut order = vec![];
+  let mut visited = HashSet::new();
+  visited.insert(root);
+
+  // the current path, with the index of the next edge to follow from each vertex
+  let mut path = vec![(root, 0)];
+  while let Some((curr, next_edge)) = path.last_mut() {
+    let curr = *curr;
+    match g.incident_edges(curr).and_then(|edges| edges.get(*next_edge)) {
+      None => {
+        path.pop();
+        order.push(curr);
+      }
+      Some((next, _edge)) => {
+        *next_edge += 1;
+        if visited.insert(next) {
+          path.push((next, 0));
+        }
+      }
+    }
+  }
+
+  order
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A loop with an if-else inside: `entry; do { A; if (..) B else C; D; E } while (..); exit`.
+  fn control_flow() -> Graph<&'static str> {
+    let mut g = Graph::new();
+    for (from, to) in [
+      ("entry", "A"),
+      ("A", "B"),
+      ("A", "C"),
+      ("B", "D"),
+      ("C", "D"),
+      ("D", "E"),
+      ("E", "A"),
+      ("E", "exit"),
+    ] {
+      g.push_edge(from, to, ());
+    }
+    g.push_vid("dead");
+    g
+  }
+
+  fn set(vids: &[&'static str]) -> HashSet<&'static str> {
+    vids.iter().copied().collect()
+  }
+
+  #[test]
+  fn dominators_test() {
+    let g = control_flow();
+    let doms = dominators(&g, &"entry");
+
+    for (vid, idom) in [
+      ("A", "entry"),
+      ("B", "A"),
+      ("C", "A"),
+      ("D", "A"),
+      ("E", "D"),
+      ("exit", "E"),
+    ] {
+      assert_eq!(doms.immediate_dominator(&vid), Some(&idom), "{}", vid);
+    }
+    assert_eq!(doms.immediate_dominator(&"entry"), None);
+    assert_eq!(doms.immediate_dominator(&"dead"), None);
+    assert_eq!(doms.dominators_of(&"exit"), [&"exit", &"E", &"D", &"A", &"entry"]);
+    assert!(doms.dominates(&"D", &"E"));
+    assert!(!doms.dominates(&"B", &"D"));
+    assert!(!doms.dominates(&"entry", &"dead"));
+
+    let frontier = |vid| doms.frontiers[&vid].iter().map(|&&vid| vid).collect::<HashSet<_>>();
+    assert_eq!(frontier("B"), set(&["D"]));
+    assert_eq!(frontier("C"), set(&["D"]));
+    assert_eq!(frontier("D"), set(&["A"]));
+    assert_eq!(frontier("A"), set(&["A"]));
+    assert_eq!(frontier("entry"), set(&[]));
+
+    let tree = doms.tree();
+    assert_eq!(tree.iter_vertices().count(), 7);
+    assert_eq!(tree.adjacent(&&"A").len(), 3);
+
+    // neither of the loop's entries dominates the other one
+    let mut g: Graph<&str> = Graph::new();
+    for (from, to) in [("entry", "X"), ("entry", "Y"), ("X", "Y"), ("Y", "X")] {
+      g.push_edge(from, to, ());
+    }
+    let doms = dominators(&g, &"entry");
+    assert_eq!(doms.immediate_dominator(&"X"), Some(&"entry"));
+    assert_eq!(doms.immediate_dominator(&"Y"), Some(&"entry"));
+
+    // a loop going back to the root ends the dominance of its vertices at the root
+    let mut g: Graph<&str> = Graph::new();
+    for (from, to) in [("entry", "A"), ("A", "B"), ("B", "entry"), ("A", "exit")] {
+      g.push_edge(from, to, ());
+    }
+    let doms = dominators(&g, &"entry");
+    let frontier = |vid| doms.frontiers[&vid].iter().map(|&&vid| vid).collect::<HashSet<_>>();
+    assert_eq!(frontier("entry"), set(&["entry"]));
+    assert_eq!(frontier("A"), set(&["entry"]));
+    assert_eq!(frontier("B"), set(&["entry"]));
+    assert_eq!(frontier("exit"), set(&[]));
+  }
+
+  #[test]
+  fn post_dominators_test() {
+    let g = control_flow();
+    let post_doms = post_dominators(&g, &"exit");
+
+    for (vid, ipdom) in [
+      ("entry", "A"),
+      ("A", "D"),
+      ("B", "D"),
+      ("C", "D"),
+      ("D", "E"),
+      ("E", "exit"),
+    ] {
+      assert_eq!(post_doms.immediate_dominator(&vid), Some(&ipdom), "{}", vid);
+    }
+    assert!(post_doms.dominates(&"D", &"A"));
+    assert!(!post_doms.dominates(&"B", &"A"));
+
+    // B and C only run depending on the branch in A, and the loop body depends on the branch in E
+    let control_dependencies = |vid| {
+      post_doms.frontiers[&vid]
+        .iter()
+        .map(|&&vid| vid)
+        .collect::<HashSet<_>>()
+    };
+    assert_eq!(control_dependencies("B"), set(&["A"]));
+    assert_eq!(control_dependencies("D"), set(&["E"]));
+
+    // the exit can have outgoing edges too, e.g. a loop around the whole function
+    let mut g: Graph<&str> = Graph::new();
+    for (from, to) in [("entry", "A"), ("A", "exit"), ("exit", "entry")] {
+      g.push_edge(from, to, ());
+    }
+    let post_doms = post_dominators(&g, &"exit");
+    for vid in ["entry", "A", "exit"] {
+      assert_eq!(
+        post_doms.frontiers[&vid]
+          .iter()
+          .map(|&&vid| vid)
+          .collect::<HashSet<_>>(),
+        set(&["exit"]),
+        "{}",
+        vid
+      );
+    }
+  }
+}
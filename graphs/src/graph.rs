@@ -1,4 +1,4 @@
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use std::hash::Hash;
 
 /// A `HashMap`-based explicitly indexed Graph representation.
@@ -92,6 +92,17 @@ where
     })
   }
 
+  /// Removes the first edge from `from` to `to`, and returns its data, if there was such an edge.
+  pub fn remove_edge(&mut self, from: &VId, to: &VId) -> Option<E> {
+    let edges = self.adjacency.get_mut(from)?;
+    let idx = edges.iter().position(|(curr_to_vid, _edge)| curr_to_vid == to)?;
+    let (_to, edge) = edges.remove(idx);
+    if edges.is_empty() {
+      self.adjacency.remove(from);
+    }
+    Some(edge)
+  }
+
   pub fn iter_edges(&self) -> impl Iterator<Item = (&VId, &Vec<(VId, E)>)> {
     self.adjacency.iter().map(|(from_vid, incident)| (from_vid, incident))
   }
@@ -124,6 +135,38 @@ where
       Some(edges) => edges.iter().map(|vid_and_e| f(vid_and_e)).collect(),
     }
   }
+
+  /// Distinct vertex ids of `self`, including those only mentioned in the edges,
+  /// in the order of the vertices first, and then of the edges.
+  pub(crate) fn all_vids(&self) -> Vec<&VId> {
+    let mut seen = FnvHashSet::default();
+    let mut vids = vec![];
+
+    let from_vertices = self.iter_vertices().map(|(vid, _)| vid);
+    let from_edges = self.iter_complete_edges().flat_map(|(from, to, _edge)| [from, to]);
+    for vid in from_vertices.chain(from_edges) {
+      if seen.insert(vid) {
+        vids.push(vid);
+      }
+    }
+
+    vids
+  }
+
+  /// Returns a graph with the same vertices as `self`, and all of its edges reversed.
+  ///
+  /// The new graph references vertex ids, vertices & edges owned by `self`.
+  pub fn reversed(&self) -> Graph<&VId, &E, &V> {
+    let mut reversed = Graph::new();
+    for (vid, vertex) in self.iter_vertices() {
+      reversed.push_vertex(vid, vertex);
+    }
+    for (from_vid, to_vid, edge) in self.iter_complete_edges() {
+      reversed.push_edge(to_vid, from_vid, edge);
+    }
+
+    reversed
+  }
 }
 
 impl<VId, E> Graph<VId, E, ()>
@@ -183,4 +226,50 @@ mod tests {
     assert_eq!(g.get_vertex(&"B"), Some(&()));
     assert_eq!(g.get_vertex(&"Z"), None);
   }
+
+  #[test]
+  fn remove_edge_test() {
+    let mut g: Graph<&str, String> = Graph::new();
+    for vid in ["A", "B", "C"] {
+      g.push_vid(vid);
+    }
+    g.push_edge("A", "B", "A -> B".to_string());
+    g.push_edge("A", "A", "A loop".to_string());
+    g.push_edge("A", "B", "A -> B again".to_string());
+    g.push_edge("C", "A", "C -> A".to_string());
+
+    assert_eq!(g.remove_edge(&"A", &"A"), Some("A loop".to_string()));
+    assert_eq!(g.remove_edge(&"A", &"A"), None);
+
+    // only the first one of the parallel edges is removed
+    assert_eq!(g.remove_edge(&"A", &"B"), Some("A -> B".to_string()));
+    assert_eq!(g.map_adjacent(&"A", |x| x.clone()), [("B", "A -> B again".to_string())]);
+
+    // the vertices stay when their last edge is removed
+    assert_eq!(g.remove_edge(&"C", &"A"), Some("C -> A".to_string()));
+    assert_eq!(g.incident_edges(&"C"), None);
+    assert!(g.has_vertex(&"C"));
+
+    assert_eq!(g.remove_edge(&"Z", &"A"), None);
+    assert_eq!(g.remove_edge(&"A", &"Z"), None);
+    assert_eq!(g.iter_complete_edges().count(), 1);
+  }
+
+  #[test]
+  fn reversed_graph() {
+    let mut g: Graph<&str, u32> = Graph::new();
+    g.push_vid("A");
+    g.push_vid("B");
+    g.push_vid("C");
+    g.push_edge("A", "B", 1);
+    g.push_edge("A", "C", 2);
+    g.push_edge("C", "C", 3);
+
+    let reversed = g.reversed();
+    assert_eq!(reversed.iter_vertices().count(), 3);
+    assert_eq!(reversed.incident_edges(&&"A"), None);
+    assert_eq!(reversed.get_edge(&"B", &"A"), Some(&&1));
+    assert_eq!(reversed.get_edge(&"C", &"A"), Some(&&2));
+    assert_eq!(reversed.get_edge(&"C", &"C"), Some(&&3));
+  }
 }
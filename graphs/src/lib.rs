@@ -1,4 +1,16 @@
 mod graph;
+mod path;
 mod spanning_trees;
 pub use graph::Graph;
+pub use path::Path;
+pub mod all_paths;
+pub mod closure;
+pub mod components;
+pub mod cycles;
+pub mod dag;
+pub mod dependency;
+pub mod dominators;
+pub mod metrics;
 pub mod search;
+pub mod shortest_paths;
+pub mod topo;
@@ -0,0 +1,280 @@
+//! Distance-based metrics of a graph: eccentricity of the vertices, and the diameter, radius,
+//! center and periphery of the whole graph.
+//!
+//! The eccentricity of a vertex is the distance from it to the farthest vertex of the graph. If some
+//! vertex can't be reached from it, the eccentricity is infinite, which is represented as `None`.
+//! For directed graphs, the distances are measured along the edges, from the vertex to the others.
+//!
+//! Exact metrics need a search from every vertex, so they are only feasible for small graphs.
+//! For huge ones, `double_sweep` gives a lower bound on the diameter with just two searches.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::search::{dijkstra, distances_from, Weight};
+use crate::Graph;
+
+/// Eccentricities of all vertices of a graph, as computed by `eccentricities` or `weighted_eccentricities`.
+#[derive(Debug, Clone)]
+pub struct Eccentricities<'a, VId, D = usize> {
+  /// Maps each vertex id to its eccentricity, or to `None` if some vertices are unreachable from it.
+  pub eccentricities: HashMap<&'a VId, Option<D>>,
+}
+
+impl<'a, VId, D> Eccentricities<'a, VId, D>
+where
+  VId: Eq + Hash,
+  D: Copy + Ord,
+{
+  pub fn eccentricity(&self, vid: &VId) -> Option<D> {
+    self.eccentricities.get(vid).copied().flatten()
+  }
+
+  /// The largest eccentricity, i.e. the distance between the farthest pair of vertices.
+  /// `None` if some vertex can't be reached from another one, or the graph is empty.
+  pub fn diameter(&self) -> Option<D> {
+    let mut diameter = None;
+    for &eccentricity in self.eccentricities.values() {
+      diameter = diameter.max(Some(eccentricity?));
+    }
+    diameter
+  }
+
+  /// The smallest eccentricity. `None` if no vertex can reach all others.
+  pub fn radius(&self) -> Option<D> {
+    self.eccentricities.values().flatten().min().copied()
+  }
+
+  /// The vertices with the smallest eccentricity, i.e. the most central ones.
+  pub fn center(&self) -> Vec<&'a VId> {
+    self.with_eccentricity(self.radius())
+  }
+
+  /// The vertices with the largest eccentricity, i.e. the endpoints of the longest shortest paths.
+  /// Empty if the diameter is infinite.
+  pub fn periphery(&self) -> Vec<&'a VId> {
+    self.with_eccentricity(self.diameter())
+  }
+
+  fn with_eccentricity(&self, target: Option<D>) -> Vec<&'a VId> {
+    match target {
+      None => vec![],
+      Some(target) => self
+        .eccentricities
+        .iter()
+        .filter(|(_vid, &eccentricity)| eccentricity == Some(target))
+        .map(|(&vid, _eccentricity)| vid)
+        .collect(),
+    }
+  }
+}
+
+/// The eccentricity of `vid` in an unweighted graph, i.e. the number of edges to the farthest vertex.
+pub fn eccentricity<'a, VId, E, V>(g: &'a Graph<VId, E, V>, vid: &'a VId) -> Option<usize>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  farthest(distances_from(g, vid), g.all_vids().len())
+}
+
+/// The eccentricity of `vid` in a graph weighted by `weight_fn`, i.e. the weight of the shortest
+/// path to the farthest vertex. Weights must not be negative.
+pub fn weighted_eccentricity<'a, VId, E, V, F, W>(g: &'a Graph<VId, E, V>, vid: &'a VId, weight_fn: F) -> Option<W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  farthest(dijkstra(g, vid, weight_fn, None, &mut ()).distances, g.all_vids().len())
+}
+
+/// Computes the eccentricities of all vertices of an unweighted graph, with a BFS from each of them.
+pub fn eccentricities<VId, E, V>(g: &Graph<VId, E, V>) -> Eccentricities<'_, VId>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let vertices = vids.len();
+  let eccentricities = vids
+    .into_iter()
+    .map(|vid| (vid, farthest(distances_from(g, vid), vertices)))
+    .collect();
+
+  Eccentricities { eccentricities }
+}
+
+/// Computes the eccentricities of all vertices of a graph weighted by `weight_fn`,
+/// with Dijkstra's algorithm from each of them. Weights must not be negative.
+pub fn weighted_eccentricities<'a, VId, E, V, F, W>(g: &'a Graph<VId, E, V>, weight_fn: F) -> Eccentricities<'a, VId, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let vids = g.all_vids();
+  let vertices = vids.len();
+  let eccentricities = vids
+    .into_iter()
+    .map(|vid| {
+      let distances = dijkstra(g, vid, &weight_fn, None, &mut ()).distances;
+      (vid, farthest(distances, vertices))
+    })
+    .collect();
+
+  Eccentricities { eccentricities }
+}
+
+/// The diameter of an unweighted graph. See `Eccentricities::diameter`.
+pub fn diameter<VId, E, V>(g: &Graph<VId, E, V>) -> Option<usize>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  eccentricities(g).diameter()
+}
+
+/// The radius of an unweighted graph. See `Eccentricities::radius`.
+pub fn radius<VId, E, V>(g: &Graph<VId, E, V>) -> Option<usize>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  eccentricities(g).radius()
+}
+
+/// The center of an unweighted graph. See `Eccentricities::center`.
+pub fn center<VId, E, V>(g: &Graph<VId, E, V>) -> Vec<&VId>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  eccentricities(g).center()
+}
+
+/// The periphery of an unweighted graph. See `Eccentricities::periphery`.
+pub fn periphery<VId, E, V>(g: &Graph<VId, E, V>) -> Vec<&VId>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  eccentricities(g).periphery()
+}
+
+/// Finds a lower bound on the diameter of an undirected unweighted graph with just two BFS runs:
+/// the first one from `start` finds the farthest vertex `a`, and the second one from `a` finds
+/// the farthest vertex from it, `b`. Returns `a`, `b`, and the distance between them.
+///
+/// The bound is exact for trees, and usually very close to the diameter for real-world networks.
+/// Only the part of the graph reachable from `start` is considered.
+pub fn double_sweep<'a, VId, E, V>(g: &'a Graph<VId, E, V>, start: &'a VId) -> (&'a VId, &'a VId, usize)
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  let (a, _) = farthest_vertex(distances_from(g, start), start);
+  let (b, distance) = farthest_vertex(distances_from(g, a), a);
+  (a, b, distance)
+}
+
+/// Same as `double_sweep`, but for a graph weighted by `weight_fn`, using Dijkstra's algorithm.
+pub fn weighted_double_sweep<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+) -> (&'a VId, &'a VId, W)
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let (a, _) = farthest_vertex(dijkstra(g, start, &weight_fn, None, &mut ()).distances, start);
+  let (b, distance) = farthest_vertex(dijkstra(g, a, &weight_fn, None, &mut ()).distances, a);
+  (a, b, distance)
+}
+
+/// The largest of the `distances`, if they cover all of the graph's `vertices`.
+fn farthest<VId, D: Copy + Ord>(distances: HashMap<&VId, D>, vertices: usize) -> Option<D> {
+  if distances.len() < vertices {
+    return None;
+  }
+  distances.values().max().copied()
+}
+
+/// The vertex with the largest distance, or `start` itself if nothing else is reachable.
+fn farthest_vertex<'a, VId, D: Copy + Ord + Default>(distances: HashMap<&'a VId, D>, start: &'a VId) -> (&'a VId, D) {
+  distances
+    .into_iter()
+    .max_by_key(|&(_vid, distance)| distance)
+    .unwrap_or((start, D::default()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  #[test]
+  fn unweighted_metrics_test() {
+    // a path A - B - C - D - E with a branch C - F
+    let mut g: Graph<&str> = Graph::new();
+    for (from, to) in [("A", "B"), ("B", "C"), ("C", "D"), ("D", "E"), ("C", "F")] {
+      g.push_undirected_edge(from, to, ());
+    }
+
+    assert_eq!(eccentricity(&g, &"A"), Some(4));
+    assert_eq!(eccentricity(&g, &"C"), Some(2));
+    assert_eq!(eccentricity(&g, &"Z"), None);
+    assert_eq!(diameter(&g), Some(4));
+    assert_eq!(radius(&g), Some(2));
+    assert_eq!(center(&g), vec![&"C"]);
+    assert_eq!(
+      periphery(&g).into_iter().collect::<HashSet<_>>(),
+      HashSet::from([&"A", &"E"])
+    );
+
+    // exact on trees, wherever it starts
+    for start in ["A", "C", "F"] {
+      let (a, b, distance) = double_sweep(&g, &start);
+      assert_eq!(distance, 4);
+      assert_eq!(HashSet::from([a, b]), HashSet::from([&"A", &"E"]));
+    }
+
+    // a directed path can only be traversed from its start
+    let mut g: Graph<&str> = Graph::new();
+    g.push_edge("A", "B", ());
+    g.push_edge("B", "C", ());
+    let metrics = eccentricities(&g);
+    assert_eq!(metrics.eccentricity(&"A"), Some(2));
+    assert_eq!(metrics.eccentricity(&"B"), None);
+    assert_eq!(metrics.diameter(), None);
+    assert_eq!(metrics.radius(), Some(2));
+    assert_eq!(metrics.center(), vec![&"A"]);
+    assert!(metrics.periphery().is_empty());
+  }
+
+  #[test]
+  fn weighted_metrics_test() {
+    // a triangle with a heavy edge, which is never on a shortest path
+    let mut g: Graph<&str, u32> = Graph::new();
+    for (from, to, weight) in [("A", "B", 1), ("B", "C", 2), ("A", "C", 10), ("C", "D", 4)] {
+      g.push_undirected_edge(from, to, weight);
+    }
+
+    assert_eq!(weighted_eccentricity(&g, &"A", |w| *w), Some(7));
+    let metrics = weighted_eccentricities(&g, |w| *w);
+    assert_eq!(metrics.diameter(), Some(7));
+    assert_eq!(metrics.radius(), Some(4));
+    assert_eq!(metrics.center(), vec![&"C"]);
+    assert_eq!(
+      metrics.periphery().into_iter().collect::<HashSet<_>>(),
+      HashSet::from([&"A", &"D"])
+    );
+
+    let (a, b, distance) = weighted_double_sweep(&g, &"B", |w| *w);
+    assert_eq!((distance, HashSet::from([a, b])), (7, HashSet::from([&"A", &"D"])));
+  }
+}
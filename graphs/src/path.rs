@@ -0,0 +1,193 @@
+use std::ops::Add;
+use std::{slice, vec};
+
+/// A path through a graph: its vertices (including both endpoints), the edges between each
+/// consecutive pair of them, and the total cost.
+///
+/// A path always has at least one vertex, and one edge less than vertices. Searches over implicit
+/// graphs, which only know the successors of each node, return paths with `()` edges, and unweighted
+/// searches use the number of edges as the cost.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Path<VId, E = (), W = usize> {
+  vertices: Vec<VId>,
+  edges: Vec<E>,
+  cost: W,
+}
+
+impl<VId, E, W> Path<VId, E, W> {
+  /// A path consisting of just the `start` vertex, with the zero cost.
+  pub fn new(start: VId) -> Self
+  where
+    W: Default,
+  {
+    Path {
+      vertices: vec![start],
+      edges: vec![],
+      cost: W::default(),
+    }
+  }
+
+  /// Builds a path from its parts.
+  ///
+  /// Panics if there are no `vertices`, or the number of `edges` isn't one less than the number of `vertices`.
+  pub fn from_parts(vertices: Vec<VId>, edges: Vec<E>, cost: W) -> Self {
+    assert!(
+      !vertices.is_empty() && edges.len() + 1 == vertices.len(),
+      "a path must have one edge less than vertices"
+    );
+
+    Path { vertices, edges, cost }
+  }
+
+  /// Extends the path by `edge` leading to `vid`, adding `weight` to its cost.
+  pub fn push(&mut self, edge: E, vid: VId, weight: W)
+  where
+    W: Add<Output = W> + Copy,
+  {
+    self.edges.push(edge);
+    self.vertices.push(vid);
+    self.cost = self.cost + weight;
+  }
+
+  pub fn start(&self) -> &VId {
+    &self.vertices[0]
+  }
+
+  pub fn end(&self) -> &VId {
+    &self.vertices[self.vertices.len() - 1]
+  }
+
+  pub fn vertices(&self) -> &[VId] {
+    &self.vertices
+  }
+
+  pub fn edges(&self) -> &[E] {
+    &self.edges
+  }
+
+  pub fn cost(&self) -> W
+  where
+    W: Copy,
+  {
+    self.cost
+  }
+
+  /// The length of the path in edges.
+  pub fn len(&self) -> usize {
+    self.edges.len()
+  }
+
+  /// Tells if the path has no edges, i.e. consists of a single vertex.
+  pub fn is_empty(&self) -> bool {
+    self.edges.is_empty()
+  }
+
+  /// Iterates over the vertices of the path.
+  pub fn iter(&self) -> slice::Iter<'_, VId> {
+    self.vertices.iter()
+  }
+
+  /// Iterates over the steps of the path as `(from, edge, to)` triples.
+  pub fn steps(&self) -> impl Iterator<Item = (&VId, &E, &VId)> {
+    self
+      .vertices
+      .windows(2)
+      .zip(&self.edges)
+      .map(|(step, edge)| (&step[0], edge, &step[1]))
+  }
+
+  /// Appends `other` to this path. Returns `None` if `other` doesn't start where this path ends.
+  pub fn concat(mut self, other: Path<VId, E, W>) -> Option<Self>
+  where
+    VId: PartialEq,
+    W: Add<Output = W>,
+  {
+    if self.end() != other.start() {
+      return None;
+    }
+
+    self.vertices.extend(other.vertices.into_iter().skip(1));
+    self.edges.extend(other.edges);
+    self.cost = self.cost + other.cost;
+    Some(self)
+  }
+
+  pub fn into_vertices(self) -> Vec<VId> {
+    self.vertices
+  }
+}
+
+impl<VId, W> Path<VId, (), W> {
+  /// Builds a path without edge data from its `vertices`.
+  ///
+  /// Panics if there are no `vertices`.
+  pub fn from_vertices(vertices: Vec<VId>, cost: W) -> Self {
+    let edges = vec![(); vertices.len().saturating_sub(1)];
+    Path::from_parts(vertices, edges, cost)
+  }
+}
+
+impl<VId, E, W> Path<&VId, E, W>
+where
+  VId: Clone,
+{
+  /// Clones the vertex ids, so that the path doesn't borrow from the graph.
+  pub fn cloned(self) -> Path<VId, E, W> {
+    Path {
+      vertices: self.vertices.into_iter().cloned().collect(),
+      edges: self.edges,
+      cost: self.cost,
+    }
+  }
+}
+
+impl<VId, E, W> IntoIterator for Path<VId, E, W> {
+  type Item = VId;
+  type IntoIter = vec::IntoIter<VId>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.vertices.into_iter()
+  }
+}
+
+impl<'p, VId, E, W> IntoIterator for &'p Path<VId, E, W> {
+  type Item = &'p VId;
+  type IntoIter = slice::Iter<'p, VId>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.vertices.iter()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn path_test() {
+    let mut first: Path<&str, char, u32> = Path::new("A");
+    assert!(first.is_empty());
+    assert_eq!((first.start(), first.end()), (&"A", &"A"));
+
+    first.push('x', "B", 2);
+    first.push('y', "C", 3);
+    assert_eq!(first.len(), 2);
+    assert_eq!(first.cost(), 5);
+    assert_eq!(first.vertices(), ["A", "B", "C"]);
+    assert_eq!(
+      first.steps().collect::<Vec<_>>(),
+      vec![(&"A", &'x', &"B"), (&"B", &'y', &"C")]
+    );
+
+    let second = Path::from_parts(vec!["C", "D"], vec!['z'], 1);
+    assert_eq!(
+      first.clone().concat(second.clone()),
+      Some(Path::from_parts(vec!["A", "B", "C", "D"], vec!['x', 'y', 'z'], 6))
+    );
+    assert_eq!(second.concat(first), None);
+
+    let unweighted = Path::from_vertices(vec![1, 2, 3], 2);
+    assert_eq!(unweighted.edges(), [(), ()]);
+    assert_eq!(unweighted.into_iter().sum::<i32>(), 6);
+  }
+}
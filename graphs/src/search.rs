@@ -1,73 +1,350 @@
 use std::{
-  collections::{HashMap, HashSet, VecDeque},
+  collections::{HashMap, VecDeque},
   hash::Hash,
+  ops::Add,
 };
 
-use crate::Graph;
+use crate::{Graph, Path};
 
+mod astar;
+mod bidirectional;
+mod deepening;
+mod dijkstra;
+mod greedy;
+mod limits;
+mod multi_source;
+mod small_weights;
+mod stats;
+mod visitor;
+mod walkers;
+pub use astar::{astar, astar_by, AStarOpts, TieBreaking};
+pub use bidirectional::{bidirectional_bfs, bidirectional_dijkstra, Meeting};
+pub use deepening::{ida_star, iddfs, DeepeningOpts};
+pub(crate) use dijkstra::dijkstra_until;
+pub use dijkstra::{dijkstra, dijkstra_targets};
+pub use greedy::{beam_search, best_first};
+pub(crate) use limits::Budget;
+pub use limits::{Limits, Progress, Status};
+pub use multi_source::{multi_source_bfs, Claim};
+pub use small_weights::{dial, zero_one_bfs};
+pub use stats::SearchStats;
+pub use visitor::{Control, Visitor};
+pub use walkers::{Bfs, Dfs, DfsPostOrder, WalkIter, Walker};
+
+/// A callback receiving the `Progress` of a search, as used by `Opts::on_progress`.
+pub type ProgressFn<'a> = Box<dyn FnMut(&Progress) + 'a>;
+
+/// Callback-based options for searches. Implements `Visitor`, mapping:
+///
+/// - `is_allowed_move(current, next)` to `examine_edge` (returning `Control::Prune` for disallowed moves);
+/// - `on_explore(parent, explored)` to `tree_edge`;
+/// - `on_progress(progress)` to `progress`.
+///
+/// `limits` are returned from `Visitor::limits`, and `stats`, if set, are collected for the search.
 #[derive(Default)]
 pub struct Opts<'a, VId> {
-  is_allowed_move: Option<Box<dyn FnMut(&VId, &VId) -> bool + 'a>>,
+  pub is_allowed_move: Option<Box<dyn FnMut(&VId, &VId) -> bool + 'a>>,
   // 'a lifetime is needed to avoid requiring static lifetime accidentally;
   // FnMut since we need to call it multiple times, can allow mutation, but don't need ownership.
-  on_explore: Option<Box<dyn FnMut(&VId, &VId) + 'a>>,
+  pub on_explore: Option<Box<dyn FnMut(&VId, &VId) + 'a>>,
+  /// Called before expanding each vertex; can be used to report the progress of long searches.
+  pub on_progress: Option<ProgressFn<'a>>,
+  pub limits: Limits,
+  pub stats: Option<SearchStats>,
+}
+
+impl<'a, VId> Visitor<VId> for Opts<'a, VId> {
+  fn discover_vertex(&mut self, vid: &VId, depth: usize) -> Control {
+    if let Some(stats) = &mut self.stats {
+      stats.discover_vertex(vid, depth);
+    }
+
+    Control::Continue
+  }
+
+  fn examine_edge(&mut self, from: &VId, to: &VId) -> Control {
+    if let Some(stats) = &mut self.stats {
+      stats.examine_edge(from, to);
+    }
+
+    let allowed = match &mut self.is_allowed_move {
+      None => true,
+      Some(is_allowed_move) => is_allowed_move(from, to),
+    };
+
+    if allowed {
+      Control::Continue
+    } else {
+      Control::Prune
+    }
+  }
+
+  fn tree_edge(&mut self, from: &VId, to: &VId) -> Control {
+    if let Some(on_explore) = &mut self.on_explore {
+      on_explore(from, to);
+    }
+
+    Control::Continue
+  }
+
+  fn limits(&self) -> Option<&Limits> {
+    Some(&self.limits)
+  }
+
+  fn progress(&mut self, progress: &Progress) -> Control {
+    if let Some(stats) = &mut self.stats {
+      Visitor::<VId>::progress(stats, progress);
+    }
+    if let Some(on_progress) = &mut self.on_progress {
+      on_progress(progress);
+    }
+
+    Control::Continue
+  }
+}
+
+/// Edge weights usable by the weighted searches.
+///
+/// `Default::default()` is used as the zero weight, so any integer type works out of the box.
+/// For floating point weights, use a wrapper implementing `Ord`.
+pub trait Weight: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<W> Weight for W where W: Copy + Ord + Add<Output = W> + Default {}
+
+/// The outcome of a search: the vertex satisfying the goal (if any), and what the search
+/// has learned about the part of the graph it explored.
+///
+/// `D` is the type of distances: depths for unweighted searches, and weights for weighted ones.
+#[derive(Debug, Clone)]
+pub struct SearchResult<'a, VId, D = usize> {
+  /// The vertex id for which the goal was reached, and its distance.
+  pub found: Option<(&'a VId, D)>,
+  /// Maps each discovered vertex id to the vertex id from which it was reached
+  /// (by the shortest known path, for weighted searches). The start vertex has no parent.
+  pub parents: HashMap<&'a VId, &'a VId>,
+  /// Maps each discovered vertex id (including the start) to its distance from the start.
+  pub distances: HashMap<&'a VId, D>,
+  /// The number of vertices taken from the queue and checked against the goal.
+  pub explored: usize,
+  /// Tells why the search has stopped.
+  pub status: Status,
+}
+
+impl<'a, VId, D> SearchResult<'a, VId, D>
+where
+  VId: Eq + Hash,
+{
+  pub fn is_found(&self) -> bool {
+    self.found.is_some()
+  }
+
+  pub(crate) fn with_status(mut self, status: Status) -> Self {
+    self.status = status;
+    self
+  }
+
+  /// Reconstructs the path from the start vertex to `vid` through the edges of `g`, the graph that
+  /// was searched, with `vid`'s distance as its cost. Returns `None` if `vid` wasn't discovered by the search.
+  ///
+  /// Of any parallel edges, the path takes the cheapest one according to `weight_fn`;
+  /// for unweighted searches, `|_| ()` takes the first one.
+  pub fn path_to<E, V, F, K>(&self, g: &'a Graph<VId, E, V>, vid: &VId, weight_fn: F) -> Option<Path<&'a VId, &'a E, D>>
+  where
+    V: Hash,
+    D: Clone,
+    F: Fn(&'a E) -> K,
+    K: Ord,
+  {
+    let (&vid, distance) = self.distances.get_key_value(vid)?;
+    Some(path_through(
+      g,
+      trace_parents(&self.parents, vid),
+      weight_fn,
+      distance.clone(),
+    ))
+  }
+}
+
+/// Follows `parents` from `vid` back to the root, and returns the path from the root to `vid`.
+pub(crate) fn trace_parents<'a, VId>(parents: &HashMap<&'a VId, &'a VId>, vid: &'a VId) -> Vec<&'a VId>
+where
+  VId: Eq + Hash,
+{
+  let mut path = vec![vid];
+  let mut curr = vid;
+  while let Some(&parent) = parents.get(curr) {
+    path.push(parent);
+    curr = parent;
+  }
+
+  path.reverse();
+  path
+}
+
+/// The cheapest of the edges from `from` to `to` according to `weight_fn`, the first one on ties.
+/// Returns `None` if there are no such edges.
+pub(crate) fn cheapest_edge<'a, VId, E, V, F, K>(
+  g: &'a Graph<VId, E, V>,
+  from: &VId,
+  to: &VId,
+  weight_fn: F,
+) -> Option<&'a E>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> K,
+  K: Ord,
+{
+  g.incident_edges(from)
+    .into_iter()
+    .flatten()
+    .filter(|(next, _edge)| next == to)
+    .map(|(_next, edge)| edge)
+    .min_by_key(|&edge| weight_fn(edge))
+}
+
+/// Builds the path through `vertices` of `g`, taking the cheapest edge (see `cheapest_edge`)
+/// between each consecutive pair of them.
+///
+/// Panics if some consecutive vertices aren't connected.
+pub(crate) fn path_through<'a, VId, E, V, F, K, D>(
+  g: &'a Graph<VId, E, V>,
+  vertices: Vec<&'a VId>,
+  weight_fn: F,
+  cost: D,
+) -> Path<&'a VId, &'a E, D>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> K,
+  K: Ord,
+{
+  let edges = vertices
+    .windows(2)
+    .map(|pair| {
+      cheapest_edge(g, pair[0], pair[1], &weight_fn).expect("consecutive vertices of a path must be connected")
+    })
+    .collect();
+  Path::from_parts(vertices, edges, cost)
 }
 
 /// Searches the graph `g` using breadth-first search start at a vertex id `start`.
 /// Stops when the `is_goal(vertex_id, depth)` function returns `true`.
-/// If graph is exhausted and nothing is found, returns `false`, otherwise returns `true`.
+/// Returns a `SearchResult`, which has `found` set if a goal was reached, and contains the
+/// parents and depths of all vertices discovered before the search stopped.
 ///
-/// Accepts an `opts` struct that may contain the following callbacks:
+/// Accepts a `visitor`, which is notified about the traversal events and can prune
+/// edges or vertices, or stop the search. `Opts` can be used to pass the following callbacks:
 ///
 /// - `is_allowed_move(current, next)` - checks if a move from `current` vertex to `next` is allowed.
 /// - `on_explore(parent, explored)` - called just before adding `explored` vertex id into the explored set.
-/// `parent` is a vertex id from which we arrived to `explored`. Note, that for the `start` vertex id this
-/// callback will not be called.
+///   `parent` is a vertex id from which we arrived to `explored`. Note, that for the `start` vertex id this
+///   callback will not be called.
+/// - `on_progress(progress)` - called before expanding each vertex.
+///
+/// The search respects the visitor's `Limits` (see `Opts::limits`); `SearchResult::status`
+/// tells if it stopped because of them.
 ///
 /// `depth` is calculated as a "level" of the graph we are exploring, counting from the `start`
 /// vertex (`depth == 0`). A vertex B reached through the vertex A via this function will have
 /// `depth_b = depth_a + 1`.
-pub fn bfs<VId, E, V, GoalFn>(g: &Graph<VId, E, V>, start: &VId, is_goal: GoalFn, opts: &mut Opts<VId>) -> bool
+pub fn bfs<'a, VId, E, V, GoalFn, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  is_goal: GoalFn,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId>
 where
   VId: Eq + Hash + Clone,
   V: Hash,
   GoalFn: Fn(&VId, usize) -> bool,
+  Vis: Visitor<VId>,
 {
-  let mut explored = HashSet::new();
-  explored.insert(start);
+  let mut result = SearchResult {
+    found: None,
+    parents: HashMap::new(),
+    distances: HashMap::new(),
+    explored: 0,
+    status: Status::Exhausted,
+  };
+  result.distances.insert(start, 0);
+  let mut budget = Budget::new(visitor);
 
+  // the third element tells if the vertex's edges should be expanded
   let mut queue = VecDeque::new();
-  queue.push_back((start, 0));
+  match visitor.discover_vertex(start, 0) {
+    Control::Break => return result.with_status(Status::Cancelled),
+    control => queue.push_back((start, 0, control == Control::Continue)),
+  }
+
+  while let Some((curr, depth, expand)) = queue.pop_front() {
+    result.explored += 1;
 
-  while let Some((curr, depth)) = queue.pop_front() {
     if is_goal(curr, depth) {
-      return true;
-    } else {
-      for next in g.adjacent(curr) {
-        let allowed = match &mut opts.is_allowed_move {
-          None => true,
-          Some(is_allowed_move) => is_allowed_move(curr, next),
-        };
-
-        if allowed && !explored.contains(next) {
-          if let Some(on_explore) = &mut opts.on_explore {
-            on_explore(curr, next);
-          }
-          explored.insert(next);
+      result.found = Some((curr, depth));
+      return result.with_status(Status::Found);
+    }
+
+    if !expand || !budget.allows_depth(depth) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, depth, queue.len()) {
+      return result.with_status(status);
+    }
 
-          queue.push_back((next, depth + 1));
+    for next in g.adjacent(curr) {
+      match visitor.examine_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      if result.distances.contains_key(next) {
+        if visitor.non_tree_edge(curr, next) == Control::Break {
+          return result.with_status(Status::Cancelled);
         }
+        continue;
+      }
+
+      match visitor.tree_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      result.distances.insert(next, depth + 1);
+      result.parents.insert(next, curr);
+
+      match visitor.discover_vertex(next, depth + 1) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        control => queue.push_back((next, depth + 1, control == Control::Continue)),
       }
     }
+
+    if visitor.finish_vertex(curr) == Control::Break {
+      return result.with_status(Status::Cancelled);
+    }
   }
 
-  return false;
+  let status = budget.exhausted();
+  result.with_status(status)
+}
+
+/// Returns the depths of all vertices reachable from `start`, as found by `bfs`.
+pub fn distances_from<'a, VId, E, V>(g: &'a Graph<VId, E, V>, start: &'a VId) -> HashMap<&'a VId, usize>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  bfs(g, start, |_, _| false, &mut ()).distances
 }
 
 /// Helper function that records paths during bfs via a hashmap `parents` that maps each `explored`
 /// vertex to the vertex from which we arrived to it (the `parent` vertex).
 ///
 /// You can use `path_from_parents` to get a path to a specific vertex.
+/// Note, that `SearchResult::parents` already contains the same information;
+/// this helper is useful when the parents need to be owned or collected across several searches.
 pub fn record_parents<'a, 'b, VId>(parents: &'b mut HashMap<VId, VId>, parent: &'a VId, explored: &'a VId)
 where
   VId: Hash + Eq + Clone,
@@ -76,12 +353,20 @@ where
 }
 
 /// Helper function that reconstructs path to a vertex `vid` using `parents` hashmap
-/// generated by the `record_parents` helper.
-pub fn path_from_parents<'a, VId>(parents: &'a HashMap<VId, VId>, vid: &'a VId) -> Vec<&'a VId>
+/// generated by the `record_parents` helper during a search of `g`. The path includes both the root and `vid`,
+/// and the first one of any parallel edges between them.
+///
+/// If `vid` has no parent, it's either the root or wasn't reached, and the path consists of just `vid`.
+pub fn path_from_parents<'a, VId, E, V>(
+  g: &'a Graph<VId, E, V>,
+  parents: &'a HashMap<VId, VId>,
+  vid: &'a VId,
+) -> Path<&'a VId, &'a E>
 where
   VId: Hash + Eq,
+  V: Hash,
 {
-  let mut path = vec![];
+  let mut path = vec![vid];
 
   let mut curr = vid;
   while let Some(parent) = parents.get(curr) {
@@ -90,21 +375,24 @@ where
   }
 
   path.reverse();
-  path
+  let len = path.len() - 1;
+  path_through(g, path, |_| (), len)
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use std::sync::atomic::{AtomicBool, Ordering};
+  use std::sync::Arc;
+  use std::time::Instant;
 
-  #[test]
-  fn bfs_test() {
-    // A directed graph with 3 layers and a cycle:
-    // Root -> L1_A, L1_B, L1_C
-    // L1_A -> L2_A, L2_B
-    // L1_B -> L2_C
-    // L2_B -> L3_A, L1_A
-    // L2_C -> L3_B
+  /// A directed graph with 3 layers and a cycle:
+  /// Root -> L1_A, L1_B, L1_C
+  /// L1_A -> L2_A, L2_B
+  /// L1_B -> L2_C
+  /// L2_B -> L3_A, L1_A
+  /// L2_C -> L3_B
+  fn layered_graph() -> Graph<&'static str> {
     let mut g = Graph::new();
     for vid in ["Root", "L1_A", "L1_B", "L1_C", "L2_A", "L2_B", "L2_C", "L3_A", "L3_B"] {
       g.push_vid(vid);
@@ -123,6 +411,13 @@ mod tests {
       g.push_edge(from, to, ());
     }
 
+    g
+  }
+
+  #[test]
+  fn bfs_test() {
+    let g = layered_graph();
+
     // finds a vertex in the last layer even in presence of cycles while recording parents
     let mut parents = HashMap::new();
     {
@@ -133,7 +428,7 @@ mod tests {
         ..Opts::default()
       };
 
-      assert!(bfs(&g, &"Root", |vid, _| *vid == "L3_B", &mut opts));
+      assert!(bfs(&g, &"Root", |vid, _| *vid == "L3_B", &mut opts).is_found());
     }
     assert_eq!(parents.len(), 8);
     assert_eq!(parents.get("Root"), None);
@@ -144,13 +439,16 @@ mod tests {
     assert_eq!(parents.get("L3_A"), Some(&"L2_B"));
     assert_eq!(parents.get("L3_B"), Some(&"L2_C"));
 
-    assert_eq!(path_from_parents(&parents, &"L3_B"), vec![&"Root", &"L1_B", &"L2_C"]);
+    let path = path_from_parents(&g, &parents, &"L3_B");
+    assert_eq!(path.vertices(), [&"Root", &"L1_B", &"L2_C", &"L3_B"]);
+    assert_eq!(path.len(), 3);
+    assert!(path_from_parents(&g, &parents, &"Root").is_empty());
 
     // finds the start vertex
-    assert!(bfs(&g, &"Root", |vid, _| *vid == "Root", &mut Opts::default()));
+    assert!(bfs(&g, &"Root", |vid, _| *vid == "Root", &mut Opts::default()).is_found());
 
     // doesn't find a vertex that doesn't exist
-    assert!(!bfs(&g, &"Root", |vid, _| *vid == "L3_C", &mut Opts::default()));
+    assert!(!bfs(&g, &"Root", |vid, _| *vid == "L3_C", &mut Opts::default()).is_found());
 
     // stops before the level 3 is reached (note, that we are using zero-based indexing).
     let mut all_explored = vec![];
@@ -160,7 +458,7 @@ mod tests {
         ..Opts::default()
       };
 
-      assert!(bfs(&g, &"Root", |_, depth| depth == 2, &mut opts));
+      assert!(bfs(&g, &"Root", |_, depth| depth == 2, &mut opts).is_found());
     }
 
     assert_eq!(all_explored.len(), 6);
@@ -176,8 +474,9 @@ mod tests {
       let mut opts = Opts {
         is_allowed_move: Some(Box::new(|_: &&str, next| next.ends_with("_A") || next.ends_with("_B"))),
         on_explore: Some(Box::new(|_, explored| all_explored.push(*explored))),
+        ..Opts::default()
       };
-      assert!(bfs(&g, &"Root", |vid, _| *vid == "L3_A", &mut opts));
+      assert!(bfs(&g, &"Root", |vid, _| *vid == "L3_A", &mut opts).is_found());
     }
 
     assert_eq!(all_explored.len(), 5);
@@ -196,7 +495,186 @@ mod tests {
         is_allowed_move: Some(Box::new(|_: &&str, next| next.ends_with("_A") || next.ends_with("_B"))),
         ..Opts::default()
       }
-    ));
+    )
+    .is_found());
+  }
+
+  #[test]
+  fn bfs_search_result_test() {
+    let g = layered_graph();
+
+    let result = bfs(&g, &"Root", |vid, _| *vid == "L3_B", &mut Opts::default());
+    assert_eq!(result.found, Some((&"L3_B", 3)));
+    assert_eq!(result.parents.get(&"L2_C"), Some(&&"L1_B"));
+    assert_eq!(result.distances.get(&"Root"), Some(&0));
+    assert_eq!(result.distances.get(&"L2_B"), Some(&2));
+    // L3_A is discovered (via L2_B) before L3_B, so every vertex gets dequeued
+    assert_eq!(result.explored, 9);
+    let path = result.path_to(&g, &"L3_B", |_| ()).unwrap();
+    assert_eq!(path.vertices(), [&"Root", &"L1_B", &"L2_C", &"L3_B"]);
+    assert_eq!(path.edges().len(), 3);
+    assert_eq!(path.cost(), 3);
+    assert_eq!(result.path_to(&g, &"Root", |_| ()), Some(Path::new(&"Root")));
+
+    // exhausting the graph still reports what was discovered
+    let result = bfs(&g, &"L1_B", |_, _| false, &mut Opts::default());
+    assert_eq!(result.found, None);
+    assert_eq!(result.distances.len(), 3);
+    assert_eq!(result.explored, 3);
+    assert_eq!(result.path_to(&g, &"L1_A", |_| ()), None);
+
+    let distances = distances_from(&g, &"L1_A");
+    assert_eq!(distances.len(), 4);
+    assert_eq!(distances.get(&"L1_A"), Some(&0));
+    assert_eq!(distances.get(&"L3_A"), Some(&2));
+  }
+
+  #[test]
+  fn bfs_limits_test() {
+    let g = layered_graph();
+    let is_l3_b = |vid: &&str, _| *vid == "L3_B";
+
+    let result = bfs(&g, &"Root", is_l3_b, &mut Opts::default());
+    assert_eq!(result.status, Status::Found);
+    let result = bfs(&g, &"L1_B", |_, _| false, &mut Opts::default());
+    assert_eq!(result.status, Status::Exhausted);
+
+    let mut opts = Opts {
+      limits: Limits {
+        max_expanded: Some(2),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    let result = bfs(&g, &"Root", is_l3_b, &mut opts);
+    assert_eq!((result.found, result.status), (None, Status::BudgetExceeded));
+    assert_eq!(result.explored, 3);
+
+    // vertices at the maximum depth are checked against the goal, but not expanded
+    let mut opts = Opts {
+      limits: Limits {
+        max_depth: Some(2),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    let result = bfs(&g, &"Root", is_l3_b, &mut opts);
+    assert_eq!((result.found, result.status), (None, Status::BudgetExceeded));
+    assert!(bfs(&g, &"Root", |vid, _| *vid == "L2_C", &mut opts).is_found());
+    opts.limits.max_depth = Some(3);
+    assert_eq!(bfs(&g, &"L1_B", |_, _| false, &mut opts).status, Status::Exhausted);
+
+    let mut opts = Opts {
+      limits: Limits {
+        deadline: Some(Instant::now()),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    assert_eq!(bfs(&g, &"Root", is_l3_b, &mut opts).status, Status::BudgetExceeded);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let mut progress = vec![];
+    {
+      let mut opts = Opts {
+        on_progress: Some(Box::new(|p: &Progress| {
+          progress.push((p.expanded, p.depth));
+          if p.expanded == 3 {
+            cancel.store(true, Ordering::Relaxed);
+          }
+        })),
+        limits: Limits {
+          cancel: Some(cancel.clone()),
+          ..Limits::default()
+        },
+        ..Opts::default()
+      };
+      assert_eq!(bfs(&g, &"Root", is_l3_b, &mut opts).status, Status::Cancelled);
+    }
+    assert_eq!(progress, vec![(1, 0), (2, 1), (3, 1)]);
+  }
+
+  #[derive(Default)]
+  struct EventLog {
+    events: Vec<String>,
+    break_on: Option<&'static str>,
+    prune: Option<&'static str>,
+  }
+
+  impl Visitor<&str> for EventLog {
+    fn discover_vertex(&mut self, vid: &&str, depth: usize) -> Control {
+      self.events.push(format!("discover {} {}", vid, depth));
+
+      if self.break_on == Some(*vid) {
+        Control::Break
+      } else if self.prune == Some(*vid) {
+        Control::Prune
+      } else {
+        Control::Continue
+      }
+    }
+
+    fn tree_edge(&mut self, from: &&str, to: &&str) -> Control {
+      self.events.push(format!("tree {} {}", from, to));
+      Control::Continue
+    }
+
+    fn non_tree_edge(&mut self, from: &&str, to: &&str) -> Control {
+      self.events.push(format!("non-tree {} {}", from, to));
+      Control::Continue
+    }
+
+    fn finish_vertex(&mut self, vid: &&str) -> Control {
+      self.events.push(format!("finish {}", vid));
+      Control::Continue
+    }
+  }
+
+  #[test]
+  fn bfs_visitor_test() {
+    let g = layered_graph();
+
+    let mut log = EventLog::default();
+    assert!(!bfs(&g, &"L1_A", |_, _| false, &mut log).is_found());
+    assert_eq!(
+      log.events,
+      vec![
+        "discover L1_A 0",
+        "tree L1_A L2_A",
+        "discover L2_A 1",
+        "tree L1_A L2_B",
+        "discover L2_B 1",
+        "finish L1_A",
+        "finish L2_A",
+        "tree L2_B L3_A",
+        "discover L3_A 2",
+        "non-tree L2_B L1_A",
+        "finish L2_B",
+        "finish L3_A",
+      ]
+    );
+
+    // breaking stops the search right away
+    let mut log = EventLog {
+      break_on: Some("L1_B"),
+      ..EventLog::default()
+    };
+    let result = bfs(&g, &"Root", |vid, _| *vid == "L3_B", &mut log);
+    assert!(!result.is_found());
+    assert_eq!(result.status, Status::Cancelled);
+    assert_eq!(log.events.last().unwrap(), "discover L1_B 1");
+    assert!(!result.distances.contains_key(&"L1_C"));
+
+    // pruned vertices are still checked against the goal, but aren't expanded
+    let mut log = EventLog {
+      prune: Some("L1_B"),
+      ..EventLog::default()
+    };
+    assert!(!bfs(&g, &"Root", |vid, _| *vid == "L3_B", &mut log).is_found());
+    assert!(bfs(&g, &"Root", |vid, _| *vid == "L1_B", &mut log).is_found());
+
+    // `()` does nothing
+    assert!(bfs(&g, &"Root", |vid, _| *vid == "L3_B", &mut ()).is_found());
   }
 
   #[test]
@@ -223,7 +701,7 @@ mod tests {
       opts.on_explore = Some(Box::new(|from_v: &&str, to_v_explored| {
         exploration_order.push((from_v.to_string(), to_v_explored.to_string()))
       }));
-      assert!(bfs(&g, &"A", |vid, _| *vid == "F", &mut opts));
+      assert!(bfs(&g, &"A", |vid, _| *vid == "F", &mut opts).is_found());
     }
     assert_eq!(
       exploration_order,
@@ -0,0 +1,578 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+use super::visitor::ByRef;
+use super::{path_through, Budget, Control, Status, Visitor, Weight};
+use crate::{Graph, Path};
+
+/// Which of the candidates with equal estimated total cost `f = g + h` A* expands first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreaking {
+  /// Prefer the candidate with the lowest heuristic, i.e. the one that is (estimated to be)
+  /// closest to the goal. Usually expands the fewest vertices.
+  #[default]
+  LowestHeuristic,
+  /// Prefer the candidate with the highest heuristic, i.e. the one closest to the start.
+  HighestHeuristic,
+  /// Prefer the candidate that was queued first.
+  Fifo,
+  /// Prefer the candidate that was queued last.
+  Lifo,
+}
+
+/// Options for `astar` and `astar_by`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AStarOpts {
+  pub tie_breaking: TieBreaking,
+  /// If `true`, debug builds check that `h(from) <= weight(from, to) + h(to)` for each explored edge,
+  /// and panic otherwise. An inconsistent heuristic still gives the cheapest path if it's admissible,
+  /// but can make A* expand the same vertex several times.
+  pub check_consistency: bool,
+}
+
+/// Finds the cheapest path from `start` to a vertex satisfying `is_goal` using A* search.
+/// `weight_fn` gives the (non-negative) weight of each edge, and `heuristic_fn` estimates the cost
+/// of the cheapest path from a vertex to a goal.
+///
+/// The heuristic must be admissible (never overestimate the cost) for the returned path to be the cheapest one.
+/// If it's also consistent (`h(from) <= weight(from, to) + h(to)` for every edge), each vertex is expanded
+/// at most once; otherwise, a vertex is expanded again whenever a cheaper path to it is found, and
+/// the visitor sees its events again. `AStarOpts::check_consistency` can be used to verify consistency
+/// in debug builds. With the heuristic always returning zero, this is Dijkstra's algorithm.
+///
+/// Returns the path from `start` to the goal through the cheapest of any parallel edges, with its weight
+/// as the cost, and the `Status` telling why the search has stopped: without a path, it's `Exhausted`
+/// only if no goal is reachable.
+///
+/// `visitor` is notified about the search events in the same way as in `dijkstra`.
+pub fn astar<'a, VId, E, V, GoalFn, F, H, W, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  is_goal: GoalFn,
+  weight_fn: F,
+  heuristic_fn: H,
+  opts: AStarOpts,
+  visitor: &mut Vis,
+) -> (Option<Path<&'a VId, &'a E, W>>, Status)
+where
+  VId: Eq + Hash,
+  V: Hash,
+  GoalFn: Fn(&VId) -> bool,
+  F: Fn(&'a E) -> W,
+  H: Fn(&VId) -> W,
+  W: Weight,
+  Vis: Visitor<VId>,
+{
+  let weight_fn = &weight_fn;
+  let successors = move |vid: &&'a VId| {
+    g.incident_edges(*vid)
+      .into_iter()
+      .flatten()
+      .map(move |(next, edge)| (next, weight_fn(edge)))
+  };
+
+  let (path, status) = astar_by(
+    start,
+    successors,
+    |vid: &&VId| is_goal(vid),
+    |vid: &&VId| heuristic_fn(vid),
+    opts,
+    &mut ByRef(visitor),
+  );
+  let path = path.map(|path| {
+    let cost = path.cost();
+    path_through(g, path.into_vertices(), weight_fn, cost)
+  });
+  (path, status)
+}
+
+/// A* search over an implicit graph, where `successors(node)` returns the nodes reachable from `node`
+/// together with the weights of the corresponding moves. Nodes are only generated when needed,
+/// so the graph can be infinite (e.g. a grid without bounds).
+///
+/// See `astar` for the details. The returned path has no edges, only the nodes.
+pub fn astar_by<N, S, I, GoalFn, H, W, Vis>(
+  start: N,
+  mut successors: S,
+  is_goal: GoalFn,
+  heuristic_fn: H,
+  opts: AStarOpts,
+  visitor: &mut Vis,
+) -> (Option<Path<N, (), W>>, Status)
+where
+  N: Eq + Hash + Clone,
+  S: FnMut(&N) -> I,
+  I: IntoIterator<Item = (N, W)>,
+  GoalFn: Fn(&N) -> bool,
+  H: Fn(&N) -> W,
+  W: Weight,
+  Vis: Visitor<N>,
+{
+  let zero = W::default();
+  let mut costs = HashMap::new();
+  let mut parents: HashMap<N, N> = HashMap::new();
+  let mut pruned = HashSet::new();
+  let mut queue = BinaryHeap::new();
+  let mut seq = 0;
+  let mut budget = Budget::new(visitor);
+
+  match visitor.discover_vertex(&start, 0) {
+    Control::Break => return (None, Status::Cancelled),
+    Control::Prune => {
+      pruned.insert(start.clone());
+    }
+    Control::Continue => (),
+  }
+  costs.insert(start.clone(), zero);
+  queue.push(Candidate {
+    heuristic: heuristic_fn(&start),
+    node: start,
+    cost: zero,
+    depth: 0,
+    seq,
+    tie_breaking: opts.tie_breaking,
+  });
+
+  while let Some(Candidate {
+    node: curr,
+    cost,
+    heuristic,
+    depth,
+    ..
+  }) = queue.pop()
+  {
+    // the same node can be queued several times, if a cheaper path to it was found later
+    if cost > costs[&curr] {
+      continue;
+    }
+
+    if is_goal(&curr) {
+      let mut path = vec![curr];
+      while let Some(parent) = parents.remove(path.last().unwrap()) {
+        path.push(parent);
+      }
+      path.reverse();
+
+      return (Some(Path::from_vertices(path, cost)), Status::Found);
+    }
+
+    if pruned.contains(&curr) || !budget.allows_depth(depth) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, depth, queue.len()) {
+      return (None, status);
+    }
+
+    for (next, weight) in successors(&curr) {
+      match visitor.examine_edge(&curr, &next) {
+        Control::Break => return (None, Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      debug_assert!(weight >= zero, "astar doesn't support negative weights");
+      let next_heuristic = heuristic_fn(&next);
+      if opts.check_consistency {
+        debug_assert!(
+          heuristic <= weight + next_heuristic,
+          "astar heuristic is inconsistent: h(from) > weight(from, to) + h(to)"
+        );
+      }
+
+      let next_cost = cost + weight;
+      // an already expanded node is expanded again, since an inconsistent heuristic can find
+      // a cheaper path to it later
+      let improves = match costs.get(&next) {
+        None => true,
+        Some(&known_cost) => next_cost < known_cost,
+      };
+      if !improves {
+        if visitor.non_tree_edge(&curr, &next) == Control::Break {
+          return (None, Status::Cancelled);
+        }
+        continue;
+      }
+
+      match visitor.tree_edge(&curr, &next) {
+        Control::Break => return (None, Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      if costs.insert(next.clone(), next_cost).is_none() {
+        match visitor.discover_vertex(&next, depth + 1) {
+          Control::Break => return (None, Status::Cancelled),
+          Control::Prune => {
+            pruned.insert(next.clone());
+          }
+          Control::Continue => (),
+        }
+      }
+      parents.insert(next.clone(), curr.clone());
+
+      seq += 1;
+      queue.push(Candidate {
+        node: next,
+        cost: next_cost,
+        heuristic: next_heuristic,
+        depth: depth + 1,
+        seq,
+        tie_breaking: opts.tie_breaking,
+      });
+    }
+
+    if visitor.finish_vertex(&curr) == Control::Break {
+      return (None, Status::Cancelled);
+    }
+  }
+
+  (None, budget.exhausted())
+}
+
+/// A node in the A*'s priority queue. `BinaryHeap` is a max-heap, so the "greatest" candidate
+/// is the one with the lowest `cost + heuristic`, with ties resolved according to `tie_breaking`.
+struct Candidate<N, W> {
+  node: N,
+  cost: W,
+  heuristic: W,
+  depth: usize,
+  seq: usize,
+  tie_breaking: TieBreaking,
+}
+
+impl<N, W: Weight> Ord for Candidate<N, W> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    let by_estimate = (other.cost + other.heuristic).cmp(&(self.cost + self.heuristic));
+
+    let by_tie_breaking = || match self.tie_breaking {
+      TieBreaking::LowestHeuristic => other.heuristic.cmp(&self.heuristic),
+      TieBreaking::HighestHeuristic => self.heuristic.cmp(&other.heuristic),
+      TieBreaking::Fifo => other.seq.cmp(&self.seq),
+      TieBreaking::Lifo => self.seq.cmp(&other.seq),
+    };
+
+    by_estimate
+      .then_with(by_tie_breaking)
+      .then_with(|| other.seq.cmp(&self.seq))
+  }
+}
+
+impl<N, W: Weight> PartialOrd for Candidate<N, W> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<N, W: Weight> PartialEq for Candidate<N, W> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<N, W: Weight> Eq for Candidate<N, W> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::{dijkstra, Limits, Opts};
+  use std::sync::atomic::AtomicBool;
+  use std::sync::Arc;
+
+  type Cell = (u32, u32);
+
+  /// Successors on a `size`x`size` grid, where cells with `col == 2 && row < 4` are walls.
+  fn grid_successors(size: u32) -> impl FnMut(&Cell) -> Vec<(Cell, u32)> {
+    move |&(row, col)| {
+      let mut next = vec![];
+      if row > 0 {
+        next.push((row - 1, col));
+      }
+      if row + 1 < size {
+        next.push((row + 1, col));
+      }
+      if col > 0 {
+        next.push((row, col - 1));
+      }
+      if col + 1 < size {
+        next.push((row, col + 1));
+      }
+
+      next
+        .into_iter()
+        .filter(|&(row, col)| !(col == 2 && row < 4))
+        .map(|cell| (cell, 1))
+        .collect()
+    }
+  }
+
+  fn manhattan(goal: Cell) -> impl Fn(&Cell) -> u32 {
+    move |&(row, col)| row.abs_diff(goal.0) + col.abs_diff(goal.1)
+  }
+
+  #[derive(Default)]
+  struct Expanded(usize);
+
+  impl<N> Visitor<N> for Expanded {
+    fn finish_vertex(&mut self, _vid: &N) -> Control {
+      self.0 += 1;
+      Control::Continue
+    }
+  }
+
+  #[test]
+  fn astar_by_grid_test() {
+    let goal = (0, 4);
+    let path = astar_by(
+      (0, 0),
+      grid_successors(6),
+      |cell| *cell == goal,
+      manhattan(goal),
+      AStarOpts::default(),
+      &mut (),
+    )
+    .0
+    .unwrap();
+
+    // goes around the wall
+    assert_eq!(path.cost(), 12);
+    assert_eq!(path.len(), 12);
+    assert_eq!(path.start(), &(0, 0));
+    assert_eq!(path.end(), &goal);
+    assert!(path.vertices().contains(&(4, 2)));
+
+    // unreachable goal on a finite grid
+    let unreachable = astar_by(
+      (0, 0),
+      grid_successors(6),
+      |cell| *cell == (9, 9),
+      |_| 0,
+      AStarOpts::default(),
+      &mut (),
+    );
+    assert_eq!(unreachable, (None, Status::Exhausted));
+  }
+
+  #[test]
+  fn astar_tie_breaking_test() {
+    let goal = (9, 9);
+    let mut expanded_by_tie_breaking = vec![];
+
+    for tie_breaking in [TieBreaking::LowestHeuristic, TieBreaking::HighestHeuristic] {
+      let mut expanded = Expanded::default();
+      let opts = AStarOpts {
+        tie_breaking,
+        check_consistency: true,
+      };
+      let open_grid = |&(row, col): &Cell| {
+        let mut next = vec![];
+        if row < 9 {
+          next.push(((row + 1, col), 1));
+        }
+        if col < 9 {
+          next.push(((row, col + 1), 1));
+        }
+        next
+      };
+
+      let path = astar_by(
+        (0, 0),
+        open_grid,
+        |cell| *cell == goal,
+        manhattan(goal),
+        opts,
+        &mut expanded,
+      )
+      .0
+      .unwrap();
+      assert_eq!(path.cost(), 18);
+      expanded_by_tie_breaking.push(expanded.0);
+    }
+
+    // preferring candidates closer to the goal goes straight to it
+    assert_eq!(expanded_by_tie_breaking[0], 18);
+    assert!(expanded_by_tie_breaking[1] > 50);
+  }
+
+  #[test]
+  #[cfg(debug_assertions)]
+  #[should_panic(expected = "heuristic is inconsistent")]
+  fn astar_consistency_check_test() {
+    let goal = (0, 4);
+    // overestimates the distance at the start by a lot
+    let heuristic = |cell: &Cell| if *cell == (0, 0) { 100 } else { 0 };
+    let opts = AStarOpts {
+      check_consistency: true,
+      ..AStarOpts::default()
+    };
+
+    astar_by(
+      (0, 0),
+      grid_successors(6),
+      |cell| *cell == goal,
+      heuristic,
+      opts,
+      &mut (),
+    );
+  }
+
+  #[test]
+  fn astar_graph_test() {
+    let mut g: Graph<&str, u32> = Graph::new();
+    for vid in ["A", "B", "C", "D", "E"] {
+      g.push_vid(vid);
+    }
+    for (from, to, weight) in [
+      ("A", "B", 1),
+      ("B", "C", 1),
+      ("C", "D", 1),
+      ("A", "C", 5),
+      ("B", "D", 7),
+      ("A", "E", 1),
+    ] {
+      g.push_edge(from, to, weight);
+    }
+
+    // a zero heuristic gives the same answer as dijkstra
+    let dijkstra_result = dijkstra(&g, &"A", |w| *w, Some(&"D"), &mut ());
+    let path = astar(
+      &g,
+      &"A",
+      |vid| *vid == "D",
+      |w| *w,
+      |_| 0,
+      AStarOpts::default(),
+      &mut (),
+    );
+    assert_eq!(path.1, Status::Found);
+    let path = path.0.unwrap();
+    assert_eq!(Some((&"D", path.cost())), dijkstra_result.found);
+    assert_eq!(path.vertices(), [&"A", &"B", &"C", &"D"]);
+    assert_eq!(path.edges(), [&1, &1, &1]);
+
+    // a heuristic that knows E is a dead end avoids expanding it
+    let heuristic = |vid: &&str| match *vid {
+      "A" => 3,
+      "B" => 2,
+      "C" => 1,
+      "E" => 100,
+      _ => 0,
+    };
+    let mut expanded = Expanded::default();
+    let path = astar(
+      &g,
+      &"A",
+      |vid| *vid == "D",
+      |w| *w,
+      heuristic,
+      AStarOpts::default(),
+      &mut expanded,
+    )
+    .0
+    .unwrap();
+    assert_eq!(path.cost(), 3);
+    assert_eq!(path.vertices(), [&"A", &"B", &"C", &"D"]);
+    assert_eq!(expanded.0, 3);
+
+    let (path, status) = astar(
+      &g,
+      &"B",
+      |vid| *vid == "A",
+      |w| *w,
+      |_| 0,
+      AStarOpts::default(),
+      &mut (),
+    );
+    assert!(path.is_none());
+    assert_eq!(status, Status::Exhausted);
+  }
+
+  #[test]
+  fn astar_inconsistent_heuristic_test() {
+    let mut g: Graph<&str, u32> = Graph::new();
+    for (from, to, weight) in [
+      ("S", "A", 1),
+      ("S", "B", 1),
+      ("A", "C", 1),
+      ("B", "C", 2),
+      ("C", "G", 3),
+    ] {
+      g.push_edge(from, to, weight);
+    }
+
+    // admissible, but not consistent: C is reached via B first, and has to be expanded again
+    let heuristic = |vid: &&str| if *vid == "A" { 4 } else { 0 };
+    let (path, status) = astar(
+      &g,
+      &"S",
+      |vid| *vid == "G",
+      |w| *w,
+      heuristic,
+      AStarOpts::default(),
+      &mut (),
+    );
+    assert_eq!(status, Status::Found);
+    let path = path.unwrap();
+    assert_eq!(path.vertices(), [&"S", &"A", &"C", &"G"]);
+    assert_eq!(path.cost(), 5);
+  }
+
+  #[test]
+  fn astar_limits_test() {
+    let goal = (5, 5);
+    let search = |limits: Limits| {
+      let mut opts = Opts {
+        limits,
+        ..Opts::default()
+      };
+      astar_by(
+        (0, 0),
+        grid_successors(6),
+        |cell| *cell == goal,
+        manhattan(goal),
+        AStarOpts::default(),
+        &mut opts,
+      )
+    };
+
+    let (path, status) = search(Limits {
+      max_expanded: Some(3),
+      ..Limits::default()
+    });
+    assert!(path.is_none());
+    assert_eq!(status, Status::BudgetExceeded);
+
+    let (path, status) = search(Limits {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Limits::default()
+    });
+    assert!(path.is_none());
+    assert_eq!(status, Status::Cancelled);
+
+    let (path, status) = search(Limits::default());
+    assert_eq!(path.unwrap().cost(), 10);
+    assert_eq!(status, Status::Found);
+
+    // the same for a graph
+    let mut g: Graph<u32, u32> = Graph::new();
+    for vid in 0..9 {
+      g.push_edge(vid, vid + 1, 1);
+    }
+    let mut opts = Opts {
+      limits: Limits {
+        max_expanded: Some(3),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    let result = astar(&g, &0, |vid| *vid == 9, |w| *w, |_| 0, AStarOpts::default(), &mut opts);
+    assert_eq!(result, (None, Status::BudgetExceeded));
+
+    opts.limits = Limits {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Limits::default()
+    };
+    let result = astar(&g, &0, |vid| *vid == 9, |w| *w, |_| 0, AStarOpts::default(), &mut opts);
+    assert_eq!(result, (None, Status::Cancelled));
+  }
+}
@@ -0,0 +1,555 @@
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
+
+use super::dijkstra::Candidate;
+use super::{path_through, trace_parents, Budget, Control, Status, Visitor, Weight};
+use crate::{Graph, Path};
+
+/// The result of a bidirectional search: the vertex where the searches from both ends met,
+/// and the shortest path going through it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Meeting<'a, VId, E, D = usize> {
+  /// The vertex reached by both the forward and the backward searches.
+  pub vertex: &'a VId,
+  /// The path from the start to the goal. Its cost is the number of edges for `bidirectional_bfs`,
+  /// and its weight for `bidirectional_dijkstra`, which takes the cheapest of any parallel edges.
+  pub path: Path<&'a VId, &'a E, D>,
+}
+
+/// Searches for the shortest path from `start` to `goal` by running breadth-first searches
+/// from both of them, expanding one level of the smaller frontier at a time until they meet.
+///
+/// The backward search follows edges of the `reversed` graph (see `Graph::reversed`), which
+/// can be built once and reused for many queries. If `reversed` is `None`, it's built from `g`
+/// for this search only.
+///
+/// `visitor` is notified about the events of both searches; edges are always passed in the
+/// direction of `g`, and the depth passed to `discover_vertex` is counted from the side
+/// that discovered the vertex.
+///
+/// Returns the meeting, if the searches have met, and the `Status` telling why they stopped.
+pub fn bidirectional_bfs<'a, VId, E, V, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  goal: &'a VId,
+  reversed: Option<&Graph<&'a VId, &'a E, &'a V>>,
+  visitor: &mut Vis,
+) -> (Option<Meeting<'a, VId, E>>, Status)
+where
+  VId: Eq + Hash,
+  V: Hash,
+  Vis: Visitor<VId>,
+{
+  if start == goal {
+    let meeting = Meeting {
+      vertex: start,
+      path: Path::new(start),
+    };
+    return (Some(meeting), Status::Found);
+  }
+
+  let built;
+  let reversed = match reversed {
+    Some(reversed) => reversed,
+    None => {
+      built = g.reversed();
+      &built
+    }
+  };
+
+  let mut forward = match Side::new(start, 0, visitor) {
+    Some(side) => side,
+    None => return (None, Status::Cancelled),
+  };
+  let mut backward = match Side::new(goal, 0, visitor) {
+    Some(side) => side,
+    None => return (None, Status::Cancelled),
+  };
+  let mut frontiers = (vec![start], vec![goal]);
+  let mut budget = Budget::new(visitor);
+
+  while !frontiers.0.is_empty() && !frontiers.1.is_empty() {
+    let is_forward = frontiers.0.len() <= frontiers.1.len();
+    let mut waiting = frontiers.0.len() + frontiers.1.len();
+    let (this, other, frontier) = if is_forward {
+      (&mut forward, &backward, &mut frontiers.0)
+    } else {
+      (&mut backward, &forward, &mut frontiers.1)
+    };
+
+    // the whole level needs to be expanded: a later vertex of it can have a shorter meeting
+    let mut best: Option<(&'a VId, usize)> = None;
+    for curr in mem::take(frontier) {
+      waiting -= 1;
+      let depth = this.distances[curr];
+      if this.pruned.contains(curr) || !budget.allows_depth(depth) {
+        continue;
+      }
+      if let Some(status) = budget.check(visitor, depth, waiting + frontier.len()) {
+        return (None, status);
+      }
+
+      for (next, _edge) in neighbours(g, reversed, curr, is_forward) {
+        let (from, to) = if is_forward { (curr, next) } else { (next, curr) };
+        match visitor.examine_edge(from, to) {
+          Control::Break => return (None, Status::Cancelled),
+          Control::Prune => continue,
+          Control::Continue => (),
+        }
+
+        if this.distances.contains_key(next) {
+          if visitor.non_tree_edge(from, to) == Control::Break {
+            return (None, Status::Cancelled);
+          }
+          continue;
+        }
+
+        match visitor.tree_edge(from, to) {
+          Control::Break => return (None, Status::Cancelled),
+          Control::Prune => continue,
+          Control::Continue => (),
+        }
+
+        if this.discover(next, curr, depth + 1, depth + 1, visitor).is_none() {
+          return (None, Status::Cancelled);
+        }
+        frontier.push(next);
+
+        if let Some(&other_depth) = other.distances.get(next) {
+          let distance = depth + 1 + other_depth;
+          let improves = match best {
+            None => true,
+            Some((_, best_distance)) => distance < best_distance,
+          };
+          if improves {
+            best = Some((next, distance));
+          }
+        }
+      }
+
+      if visitor.finish_vertex(curr) == Control::Break {
+        return (None, Status::Cancelled);
+      }
+    }
+
+    if let Some((vertex, distance)) = best {
+      return (
+        Some(meet(g, &forward, &backward, vertex, distance, |_| ())),
+        Status::Found,
+      );
+    }
+  }
+
+  (None, budget.exhausted())
+}
+
+/// Searches for the shortest path from `start` to `goal` by running Dijkstra's algorithm
+/// from both of them, with `weight_fn` giving the weight of each edge. Weights must not be negative.
+///
+/// Each step settles a vertex on the side with the closer frontier, and the search stops once
+/// the sum of both frontiers' distances can't improve the shortest path found so far.
+///
+/// `reversed` and `visitor` are used in the same way as in `bidirectional_bfs`.
+pub fn bidirectional_dijkstra<'a, VId, E, V, F, W, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  goal: &'a VId,
+  reversed: Option<&Graph<&'a VId, &'a E, &'a V>>,
+  visitor: &mut Vis,
+) -> (Option<Meeting<'a, VId, E, W>>, Status)
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+  Vis: Visitor<VId>,
+{
+  let zero = W::default();
+  if start == goal {
+    let meeting = Meeting {
+      vertex: start,
+      path: Path::new(start),
+    };
+    return (Some(meeting), Status::Found);
+  }
+
+  let built;
+  let reversed = match reversed {
+    Some(reversed) => reversed,
+    None => {
+      built = g.reversed();
+      &built
+    }
+  };
+
+  let mut forward = match Side::new(start, zero, visitor) {
+    Some(side) => side,
+    None => return (None, Status::Cancelled),
+  };
+  let mut backward = match Side::new(goal, zero, visitor) {
+    Some(side) => side,
+    None => return (None, Status::Cancelled),
+  };
+  let mut queues = (BinaryHeap::new(), BinaryHeap::new());
+  for (queue, vid) in [(&mut queues.0, start), (&mut queues.1, goal)] {
+    queue.push(Candidate {
+      vid,
+      distance: zero,
+      depth: 0,
+    });
+  }
+  let mut settled = (HashSet::new(), HashSet::new());
+  let mut best: Option<(&'a VId, W)> = None;
+  let mut budget = Budget::new(visitor);
+
+  // once a side is exhausted, no other path can connect the searches
+  while let (Some(forward_top), Some(backward_top)) = (queues.0.peek(), queues.1.peek()) {
+    if let Some((_, best_distance)) = best {
+      if forward_top.distance + backward_top.distance >= best_distance {
+        break;
+      }
+    }
+
+    let is_forward = forward_top.distance <= backward_top.distance;
+    // both queues are the frontier, without the candidate popped below
+    let waiting = queues.0.len() + queues.1.len() - 1;
+    let (this, other, queue, settled) = if is_forward {
+      (&mut forward, &backward, &mut queues.0, &mut settled.0)
+    } else {
+      (&mut backward, &forward, &mut queues.1, &mut settled.1)
+    };
+
+    let Candidate {
+      vid: curr,
+      distance,
+      depth,
+    } = queue.pop().unwrap();
+    // the same vertex can be queued several times, if a shorter path to it was found later
+    if !settled.insert(curr) || this.pruned.contains(curr) || !budget.allows_depth(depth) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, depth, waiting) {
+      return (None, status);
+    }
+
+    for (next, edge) in neighbours(g, reversed, curr, is_forward) {
+      let (from, to) = if is_forward { (curr, next) } else { (next, curr) };
+      match visitor.examine_edge(from, to) {
+        Control::Break => return (None, Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      let weight = weight_fn(edge);
+      debug_assert!(weight >= zero, "dijkstra doesn't support negative weights");
+      let next_distance = distance + weight;
+
+      let improves = !settled.contains(next)
+        && match this.distances.get(next) {
+          None => true,
+          Some(&known_distance) => next_distance < known_distance,
+        };
+      if improves {
+        match visitor.tree_edge(from, to) {
+          Control::Break => return (None, Status::Cancelled),
+          Control::Prune => continue,
+          Control::Continue => (),
+        }
+
+        if this.distances.contains_key(next) {
+          this.distances.insert(next, next_distance);
+          this.parents.insert(next, curr);
+        } else {
+          if this.discover(next, curr, next_distance, depth + 1, visitor).is_none() {
+            return (None, Status::Cancelled);
+          }
+        }
+
+        queue.push(Candidate {
+          vid: next,
+          distance: next_distance,
+          depth: depth + 1,
+        });
+      } else if visitor.non_tree_edge(from, to) == Control::Break {
+        return (None, Status::Cancelled);
+      }
+
+      // using the known distance instead of `next_distance` keeps it consistent with the parents
+      if let (Some(&this_distance), Some(&other_distance)) = (this.distances.get(next), other.distances.get(next)) {
+        let distance = this_distance + other_distance;
+        let improves = match best {
+          None => true,
+          Some((_, best_distance)) => distance < best_distance,
+        };
+        if improves {
+          best = Some((next, distance));
+        }
+      }
+    }
+
+    if visitor.finish_vertex(curr) == Control::Break {
+      return (None, Status::Cancelled);
+    }
+  }
+
+  match best {
+    Some((vertex, distance)) => {
+      let meeting = meet(g, &forward, &backward, vertex, distance, weight_fn);
+      (Some(meeting), Status::Found)
+    }
+    None => (None, budget.exhausted()),
+  }
+}
+
+/// What one of the directions of a bidirectional search knows.
+struct Side<'a, VId, D> {
+  distances: HashMap<&'a VId, D>,
+  parents: HashMap<&'a VId, &'a VId>,
+  pruned: HashSet<&'a VId>,
+}
+
+impl<'a, VId, D> Side<'a, VId, D>
+where
+  VId: Eq + Hash,
+{
+  /// Starts a side from its `root`. Returns `None` if the visitor stops the search.
+  fn new<Vis>(root: &'a VId, zero: D, visitor: &mut Vis) -> Option<Side<'a, VId, D>>
+  where
+    Vis: Visitor<VId>,
+  {
+    let mut side = Side {
+      distances: HashMap::new(),
+      parents: HashMap::new(),
+      pruned: HashSet::new(),
+    };
+    side.distances.insert(root, zero);
+
+    match visitor.discover_vertex(root, 0) {
+      Control::Break => None,
+      Control::Prune => {
+        side.pruned.insert(root);
+        Some(side)
+      }
+      Control::Continue => Some(side),
+    }
+  }
+
+  /// Records a newly discovered vertex. Returns `None` if the visitor stops the search.
+  fn discover<Vis>(&mut self, vid: &'a VId, parent: &'a VId, distance: D, depth: usize, visitor: &mut Vis) -> Option<()>
+  where
+    Vis: Visitor<VId>,
+  {
+    self.distances.insert(vid, distance);
+    self.parents.insert(vid, parent);
+
+    match visitor.discover_vertex(vid, depth) {
+      Control::Break => return None,
+      Control::Prune => {
+        self.pruned.insert(vid);
+      }
+      Control::Continue => (),
+    }
+
+    Some(())
+  }
+}
+
+/// Outgoing edges of `vid` for the forward search, and incoming edges for the backward one.
+fn neighbours<'a, 'r, VId, E, V>(
+  g: &'a Graph<VId, E, V>,
+  reversed: &'r Graph<&'a VId, &'a E, &'a V>,
+  vid: &'a VId,
+  is_forward: bool,
+) -> impl Iterator<Item = (&'a VId, &'a E)> + 'r
+where
+  'a: 'r,
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let (outgoing, incoming) = if is_forward {
+    (g.incident_edges(vid), None)
+  } else {
+    (None, reversed.incident_edges(&vid))
+  };
+
+  outgoing
+    .into_iter()
+    .flatten()
+    .map(|(to, edge)| (to, edge))
+    .chain(incoming.into_iter().flatten().copied())
+}
+
+fn meet<'a, VId, E, V, D, F, K>(
+  g: &'a Graph<VId, E, V>,
+  forward: &Side<'a, VId, D>,
+  backward: &Side<'a, VId, D>,
+  vertex: &'a VId,
+  distance: D,
+  weight_fn: F,
+) -> Meeting<'a, VId, E, D>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> K,
+  K: Ord,
+{
+  let mut path = trace_parents(&forward.parents, vertex);
+  // the backward search's parents lead from the meeting vertex towards the goal
+  path.extend(trace_parents(&backward.parents, vertex).into_iter().rev().skip(1));
+
+  Meeting {
+    vertex,
+    path: path_through(g, path, weight_fn, distance),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::{bfs, dijkstra, Limits, Opts};
+  use std::sync::atomic::AtomicBool;
+  use std::sync::Arc;
+
+  /// A directed ring 0 -> 1 -> ... -> 9 -> 0, with weights equal to the source vertex,
+  /// and shortcuts 0 -> 5 (weight 20) and 2 -> 7 (weight 3).
+  fn ring() -> Graph<u32, u32> {
+    let mut g = Graph::new();
+    for vid in 0..10 {
+      g.push_vid(vid);
+      g.push_edge(vid, (vid + 1) % 10, vid);
+    }
+    g.push_edge(0, 5, 20);
+    g.push_edge(2, 7, 3);
+
+    g
+  }
+
+  #[test]
+  fn bidirectional_bfs_test() {
+    let g = ring();
+    let reversed = g.reversed();
+
+    let meeting = bidirectional_bfs(&g, &0, &8, Some(&reversed), &mut ()).0.unwrap();
+    // both 0 -> 5 -> ... -> 8 and 0 -> 1 -> 2 -> 7 -> 8 are the shortest
+    assert_eq!(meeting.path.cost(), 4);
+    assert_eq!(meeting.path.len(), 4);
+    assert!(meeting.path.vertices().contains(&meeting.vertex));
+
+    // agrees with plain BFS on every pair
+    for start in 0..10 {
+      for goal in 0..10 {
+        let meeting = bidirectional_bfs(&g, &start, &goal, Some(&reversed), &mut ())
+          .0
+          .unwrap();
+        let result = bfs(&g, &start, |vid, _| *vid == goal, &mut ());
+        assert_eq!(Some((&goal, meeting.path.cost())), result.found);
+        assert_eq!(meeting.path.len(), meeting.path.cost());
+        assert!(meeting
+          .path
+          .steps()
+          .all(|(from, _, to)| g.get_edge(**from, **to).is_some()));
+
+        // the directed graph is reversed internally when it's not given
+        let meeting = bidirectional_bfs(&g, &start, &goal, None, &mut ()).0.unwrap();
+        assert_eq!(Some((&goal, meeting.path.cost())), result.found);
+      }
+    }
+
+    // visitor's moves are in the direction of the graph's edges
+    let mut opts = Opts {
+      is_allowed_move: Some(Box::new(|from: &u32, to: &u32| (*from, *to) != (0, 5))),
+      ..Opts::default()
+    };
+    let meeting = bidirectional_bfs(&g, &0, &8, Some(&reversed), &mut opts).0.unwrap();
+    assert_eq!(meeting.path.vertices(), [&0, &1, &2, &7, &8]);
+
+    let mut g = g;
+    g.push_vid(10);
+    assert_eq!(
+      bidirectional_bfs(&g, &0, &10, Some(&g.reversed()), &mut ()),
+      (None, Status::Exhausted)
+    );
+    assert_eq!(
+      bidirectional_bfs(&g, &10, &10, None, &mut ()).0.unwrap().path,
+      Path::new(&10)
+    );
+  }
+
+  #[test]
+  fn bidirectional_dijkstra_test() {
+    let g = ring();
+    let reversed = g.reversed();
+
+    let meeting = bidirectional_dijkstra(&g, &0, |w| *w, &8, Some(&reversed), &mut ())
+      .0
+      .unwrap();
+    assert_eq!(meeting.path.cost(), 11);
+    assert_eq!(meeting.path.vertices(), [&0, &1, &2, &7, &8]);
+
+    for start in 0..10 {
+      for goal in 0..10 {
+        let meeting = bidirectional_dijkstra(&g, &start, |w| *w, &goal, Some(&reversed), &mut ())
+          .0
+          .unwrap();
+        let result = dijkstra(&g, &start, |w| *w, Some(&goal), &mut ());
+        assert_eq!(Some((&goal, meeting.path.cost())), result.found);
+
+        let weight = meeting.path.edges().iter().copied().sum::<u32>();
+        assert_eq!(weight, meeting.path.cost());
+
+        let meeting = bidirectional_dijkstra(&g, &start, |w| *w, &goal, None, &mut ())
+          .0
+          .unwrap();
+        assert_eq!(Some((&goal, meeting.path.cost())), result.found);
+      }
+    }
+
+    // the reversed graph is built when it's not given
+    let mut g: Graph<&str, u32> = Graph::new();
+    for (from, to, weight) in [("A", "B", 2), ("B", "C", 2), ("A", "C", 5), ("C", "D", 1)] {
+      g.push_undirected_edge(from, to, weight);
+    }
+    let meeting = bidirectional_dijkstra(&g, &"D", |w| *w, &"A", None, &mut ()).0.unwrap();
+    assert_eq!(meeting.path.cost(), 5);
+  }
+
+  #[test]
+  fn bidirectional_limits_test() {
+    let g = ring();
+    let reversed = g.reversed();
+    let mut opts = Opts {
+      limits: Limits {
+        max_expanded: Some(2),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    assert_eq!(
+      bidirectional_bfs(&g, &0, &8, Some(&reversed), &mut opts),
+      (None, Status::BudgetExceeded)
+    );
+    assert_eq!(
+      bidirectional_dijkstra(&g, &0, |w| *w, &8, Some(&reversed), &mut opts),
+      (None, Status::BudgetExceeded)
+    );
+
+    opts.limits = Limits {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Limits::default()
+    };
+    assert_eq!(
+      bidirectional_bfs(&g, &0, &8, Some(&reversed), &mut opts),
+      (None, Status::Cancelled)
+    );
+    assert_eq!(
+      bidirectional_dijkstra(&g, &0, |w| *w, &8, Some(&reversed), &mut opts),
+      (None, Status::Cancelled)
+    );
+
+    let (meeting, status) = bidirectional_dijkstra(&g, &0, |w| *w, &8, Some(&reversed), &mut ());
+    assert_eq!(meeting.unwrap().path.cost(), 11);
+    assert_eq!(status, Status::Found);
+  }
+}
@@ -0,0 +1,376 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::vec;
+
+use super::{Budget, Control, Status, Visitor, Weight};
+use crate::Path;
+
+/// Options for `iddfs` and `ida_star`. `B` is the type of the bound limiting each iteration:
+/// the depth for `iddfs`, and the estimated cost of a path for `ida_star`.
+#[derive(Default)]
+pub struct DeepeningOpts<'a, B> {
+  /// If `true`, remembers the cost at which each node was reached during an iteration, and doesn't
+  /// expand the node again when it's reached at the same or a higher cost. This avoids re-exploring
+  /// the states reachable in several ways, at the cost of keeping them in memory.
+  /// Without it, only the nodes of the current path are remembered, to avoid going in cycles.
+  pub transposition_table: bool,
+  /// The search gives up once the bound exceeds `max_bound`, with `Status::BudgetExceeded`.
+  pub max_bound: Option<B>,
+  /// Called with the bound at the start of each iteration. Returning `Control::Break` stops the search,
+  /// with `Status::Cancelled`.
+  pub on_bound: Option<Box<dyn FnMut(B) -> Control + 'a>>,
+}
+
+/// Iterative deepening depth-first search over an implicit graph, where `successors(node)` returns
+/// the nodes reachable from `node`. Runs depth-first searches limited to depth 0, 1, 2, and so on,
+/// until a node satisfying `is_goal` is found.
+///
+/// Finds a path with the fewest edges, like `bfs`, but only keeps the current path in memory
+/// (unless `DeepeningOpts::transposition_table` is set), which suits state spaces with a huge
+/// branching factor. The price is re-expanding the shallow nodes in each iteration.
+///
+/// If no goal is reachable in an infinite graph, the search only ends when it reaches
+/// `DeepeningOpts::max_bound` or one of the visitor's limits. The returned `Status` tells
+/// these cases apart from a finite graph without a reachable goal, which is `Exhausted`.
+///
+/// `visitor` is notified about the events of each iteration, so the nodes can be discovered many times.
+pub fn iddfs<N, S, I, GoalFn, Vis>(
+  start: N,
+  mut successors: S,
+  is_goal: GoalFn,
+  opts: DeepeningOpts<usize>,
+  visitor: &mut Vis,
+) -> (Option<Path<N>>, Status)
+where
+  N: Eq + Hash + Clone,
+  S: FnMut(&N) -> I,
+  I: IntoIterator<Item = N>,
+  GoalFn: Fn(&N) -> bool,
+  Vis: Visitor<N>,
+{
+  let successors = |node: &N| successors(node).into_iter().map(|next| (next, 1));
+  ida_star(start, successors, is_goal, |_| 0, opts, visitor)
+}
+
+/// Iterative deepening A* (IDA*) over an implicit graph, where `successors(node)` returns the nodes
+/// reachable from `node` together with the (non-negative) weights of the corresponding moves.
+///
+/// Each iteration is a depth-first search that doesn't go past the nodes whose estimated total cost
+/// `f = g + h` exceeds the bound. The first bound is the heuristic of `start`, and each next one is
+/// the smallest `f` that exceeded the previous bound. With an admissible heuristic, the returned path
+/// is the cheapest one, as with `astar_by`, but the memory use is only proportional to its length.
+///
+/// See `iddfs` for the details.
+pub fn ida_star<N, S, I, GoalFn, H, W, Vis>(
+  start: N,
+  successors: S,
+  is_goal: GoalFn,
+  heuristic_fn: H,
+  mut opts: DeepeningOpts<W>,
+  visitor: &mut Vis,
+) -> (Option<Path<N, (), W>>, Status)
+where
+  N: Eq + Hash + Clone,
+  S: FnMut(&N) -> I,
+  I: IntoIterator<Item = (N, W)>,
+  GoalFn: Fn(&N) -> bool,
+  H: Fn(&N) -> W,
+  W: Weight,
+  Vis: Visitor<N>,
+{
+  let mut deepening = Deepening {
+    budget: Budget::new(visitor),
+    successors,
+    is_goal,
+    heuristic_fn,
+    transposition_table: opts.transposition_table,
+    visitor,
+  };
+
+  let mut bound = (deepening.heuristic_fn)(&start);
+  loop {
+    if let Some(max_bound) = opts.max_bound {
+      if bound > max_bound {
+        return (None, Status::BudgetExceeded);
+      }
+    }
+    if let Some(on_bound) = &mut opts.on_bound {
+      if on_bound(bound) == Control::Break {
+        return (None, Status::Cancelled);
+      }
+    }
+
+    match deepening.iteration(&start, bound) {
+      Iteration::Found(path) => return (Some(path), Status::Found),
+      Iteration::Exceeded(next_bound) => bound = next_bound,
+      Iteration::Stopped(status) => return (None, status),
+    }
+  }
+}
+
+/// How an iteration of `ida_star` has ended.
+enum Iteration<N, W> {
+  Found(Path<N, (), W>),
+  /// No goal within the bound; the next bound is the smallest estimated cost that exceeded it.
+  Exceeded(W),
+  /// The search can't continue, for the given reason: there are no nodes past the bound,
+  /// or the search was cancelled or ran out of budget.
+  Stopped(Status),
+}
+
+/// A node of the current path, with the cost of reaching it and its unexplored successors.
+type Frame<N, W> = (N, W, vec::IntoIter<(N, W)>);
+
+struct Deepening<'v, S, GoalFn, H, Vis> {
+  successors: S,
+  is_goal: GoalFn,
+  heuristic_fn: H,
+  transposition_table: bool,
+  visitor: &'v mut Vis,
+  budget: Budget,
+}
+
+impl<'v, S, GoalFn, H, Vis> Deepening<'v, S, GoalFn, H, Vis> {
+  /// A depth-first search from `start` that doesn't go past the nodes with `f > bound`.
+  fn iteration<N, I, W>(&mut self, start: &N, bound: W) -> Iteration<N, W>
+  where
+    N: Eq + Hash + Clone,
+    S: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, W)>,
+    GoalFn: Fn(&N) -> bool,
+    H: Fn(&N) -> W,
+    W: Weight,
+    Vis: Visitor<N>,
+  {
+    let mut next_bound: Option<W> = None;
+    let mut table: HashMap<N, W> = HashMap::new();
+    let mut path: Vec<Frame<N, W>> = vec![];
+    let mut on_path = HashSet::new();
+    let mut waiting = 0;
+
+    let mut entering = Some((start.clone(), W::default()));
+    loop {
+      if let Some((node, cost)) = entering.take() {
+        let depth = path.len();
+        let estimate = cost + (self.heuristic_fn)(&node);
+        let transposed = match table.get(&node) {
+          Some(&known_cost) => cost >= known_cost,
+          None => false,
+        };
+
+        if estimate > bound {
+          next_bound = Some(match next_bound {
+            Some(next_bound) if next_bound <= estimate => next_bound,
+            _ => estimate,
+          });
+        } else if !transposed {
+          let control = self.visitor.discover_vertex(&node, depth);
+          if control == Control::Break {
+            return Iteration::Stopped(Status::Cancelled);
+          }
+
+          if (self.is_goal)(&node) {
+            let vertices = path.into_iter().map(|(node, _, _)| node).chain(Some(node)).collect();
+            return Iteration::Found(Path::from_vertices(vertices, cost));
+          }
+
+          if control == Control::Continue && self.budget.allows_depth(depth) {
+            if let Some(status) = self.budget.check(self.visitor, depth, waiting) {
+              return Iteration::Stopped(status);
+            }
+
+            if self.transposition_table {
+              table.insert(node.clone(), cost);
+            }
+            let successors = (self.successors)(&node).into_iter().collect::<Vec<_>>();
+            waiting += successors.len();
+            on_path.insert(node.clone());
+            path.push((node, cost, successors.into_iter()));
+          }
+        }
+      }
+
+      let (curr, cost, successors) = match path.last_mut() {
+        None => break,
+        Some(top) => top,
+      };
+      match successors.next() {
+        None => {
+          let (curr, _, _) = path.pop().unwrap();
+          on_path.remove(&curr);
+          if self.visitor.finish_vertex(&curr) == Control::Break {
+            return Iteration::Stopped(Status::Cancelled);
+          }
+        }
+        Some((next, weight)) => {
+          waiting -= 1;
+          debug_assert!(weight >= W::default(), "ida_star doesn't support negative weights");
+
+          match self.visitor.examine_edge(curr, &next) {
+            Control::Break => return Iteration::Stopped(Status::Cancelled),
+            Control::Prune => continue,
+            Control::Continue => (),
+          }
+
+          // going back to a node of the current path would be a cycle
+          if on_path.contains(&next) {
+            if self.visitor.non_tree_edge(curr, &next) == Control::Break {
+              return Iteration::Stopped(Status::Cancelled);
+            }
+            continue;
+          }
+
+          match self.visitor.tree_edge(curr, &next) {
+            Control::Break => return Iteration::Stopped(Status::Cancelled),
+            Control::Prune => continue,
+            Control::Continue => (),
+          }
+
+          entering = Some((next, *cost + weight));
+        }
+      }
+    }
+
+    match next_bound {
+      Some(next_bound) => Iteration::Exceeded(next_bound),
+      None => Iteration::Stopped(self.budget.exhausted()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::{astar_by, AStarOpts, Limits, Opts, SearchStats};
+  use std::cell::RefCell;
+  use std::sync::atomic::AtomicBool;
+  use std::sync::Arc;
+
+  /// Moves on a number line: `n -> n + 1` costs 1, and `n -> 2n` costs 3.
+  fn moves(n: &u32) -> Vec<(u32, u32)> {
+    vec![(n + 1, 1), (n * 2, 3)]
+  }
+
+  #[test]
+  fn iddfs_test() {
+    let bounds = RefCell::new(vec![]);
+    let opts = DeepeningOpts {
+      on_bound: Some(Box::new(|bound| {
+        bounds.borrow_mut().push(bound);
+        Control::Continue
+      })),
+      ..DeepeningOpts::default()
+    };
+
+    let (path, status) = iddfs(1, |n: &u32| vec![n + 1, n * 2], |n| *n == 10, opts, &mut ());
+    assert_eq!(status, Status::Found);
+    let path = path.unwrap();
+    assert_eq!(path.vertices(), [1, 2, 4, 5, 10]);
+    assert_eq!(path.cost(), 4);
+    assert_eq!(bounds.into_inner(), vec![0, 1, 2, 3, 4]);
+
+    // remembering the visited states avoids expanding the same numbers in several ways
+    let mut without_table = SearchStats::default();
+    let mut with_table = SearchStats::default();
+    let opts = DeepeningOpts {
+      transposition_table: true,
+      ..DeepeningOpts::default()
+    };
+    let grid = |&(row, col): &(i32, i32)| vec![(row + 1, col), (row, col + 1)];
+    let goal = |cell: &(i32, i32)| *cell == (5, 5);
+    let path = iddfs((0, 0), grid, goal, DeepeningOpts::default(), &mut without_table)
+      .0
+      .unwrap();
+    assert_eq!(
+      iddfs((0, 0), grid, goal, opts, &mut with_table).0.unwrap().len(),
+      path.len()
+    );
+    assert!(with_table.expanded * 5 < without_table.expanded);
+
+    // an unreachable goal in an infinite graph
+    let opts = DeepeningOpts {
+      max_bound: Some(20),
+      ..DeepeningOpts::default()
+    };
+    assert_eq!(
+      iddfs(1, |n: &u32| vec![n * 2], |n| *n == 10, opts, &mut ()),
+      (None, Status::BudgetExceeded)
+    );
+  }
+
+  #[test]
+  fn ida_star_test() {
+    for target in [10, 17, 24, 31] {
+      let expected = astar_by(1, moves, |n| *n == target, |_| 0, AStarOpts::default(), &mut ())
+        .0
+        .unwrap();
+
+      // neither of the moves can reach the target cheaper than a step per halving
+      let heuristic = |n: &u32| {
+        if *n >= target {
+          0
+        } else {
+          (32 - (target / n).leading_zeros()) - 1
+        }
+      };
+      for transposition_table in [false, true] {
+        let opts = DeepeningOpts {
+          transposition_table,
+          ..DeepeningOpts::default()
+        };
+        let path = ida_star(1, moves, |n| *n == target, heuristic, opts, &mut ())
+          .0
+          .unwrap();
+        assert_eq!(path.cost(), expected.cost());
+        assert_eq!((path.start(), path.end()), (&1, &target));
+      }
+    }
+
+    // moves past the target never lead back to it
+    let opts = DeepeningOpts {
+      max_bound: Some(100),
+      ..DeepeningOpts::default()
+    };
+    let successors = |n: &u32| moves(n).into_iter().filter(|&(next, _)| next <= 12);
+    assert_eq!(
+      ida_star(13, successors, |n| *n == 12, |_| 0, opts, &mut ()),
+      (None, Status::Exhausted)
+    );
+  }
+
+  #[test]
+  fn deepening_limits_test() {
+    let successors = |n: &u32| vec![n + 1, n * 2];
+    let goal = |n: &u32| *n == 1000;
+
+    let mut opts = Opts {
+      limits: Limits {
+        max_expanded: Some(50),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    let result = iddfs(1, successors, goal, DeepeningOpts::default(), &mut opts);
+    assert_eq!(result, (None, Status::BudgetExceeded));
+    let result = ida_star(1, moves, goal, |_| 0, DeepeningOpts::default(), &mut opts);
+    assert_eq!(result, (None, Status::BudgetExceeded));
+
+    opts.limits = Limits {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Limits::default()
+    };
+    let result = iddfs(1, successors, goal, DeepeningOpts::default(), &mut opts);
+    assert_eq!(result, (None, Status::Cancelled));
+    let result = ida_star(1, moves, goal, |_| 0, DeepeningOpts::default(), &mut opts);
+    assert_eq!(result, (None, Status::Cancelled));
+
+    // stopping at a bound is a cancellation too
+    let opts = DeepeningOpts {
+      on_bound: Some(Box::new(
+        |bound| if bound < 3 { Control::Continue } else { Control::Break },
+      )),
+      ..DeepeningOpts::default()
+    };
+    assert_eq!(iddfs(1, successors, goal, opts, &mut ()), (None, Status::Cancelled));
+  }
+}
@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+use super::{cheapest_edge, Budget, Control, SearchResult, Status, Visitor, Weight};
+use crate::Graph;
+
+/// Finds the shortest paths from `start` using Dijkstra's algorithm, with `weight_fn` giving
+/// the weight of each edge. Weights must not be negative.
+///
+/// If `goal` is given, the search stops as soon as the shortest path to it is known,
+/// and the result's `found` contains it with its distance. Otherwise, all vertices reachable
+/// from `start` are explored, and `distances` & `parents` describe the whole shortest path tree.
+///
+/// `visitor` is notified about the search events: `tree_edge` is called for each edge that
+/// improves the distance to the vertex it leads to, `non_tree_edge` for all other examined edges,
+/// `discover_vertex` when a vertex is reached for the first time (with its depth in edges),
+/// and `finish_vertex` once a vertex's distance is final and its edges were examined.
+pub fn dijkstra<'a, VId, E, V, F, W, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  goal: Option<&VId>,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+  Vis: Visitor<VId>,
+{
+  let is_goal = |vid: &VId| goal == Some(vid);
+  dijkstra_until(g, start, |_, _, edge| weight_fn(edge), is_goal, visitor)
+}
+
+/// Same as `dijkstra`, but stops once the shortest paths to all of the `targets` are known.
+/// In that case, `found` contains the target that was reached last, i.e. the farthest one.
+/// If some of the targets are unreachable, the whole reachable part of the graph is explored
+/// and `found` is `None`. With no `targets`, all of them are reached at once: the search stops
+/// at `start`, which is `found` with the zero distance.
+pub fn dijkstra_targets<'a, VId, E, V, F, W, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  targets: &[VId],
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+  Vis: Visitor<VId>,
+{
+  let mut remaining = targets.iter().collect::<HashSet<_>>();
+  let is_goal = |vid: &VId| {
+    remaining.remove(vid);
+    remaining.is_empty()
+  };
+  dijkstra_until(g, start, |_, _, edge| weight_fn(edge), is_goal, visitor)
+}
+
+/// Dijkstra's algorithm, stopping once `is_goal` returns `true` for a vertex with a known shortest path.
+/// `weight_fn(from, to, edge)` can take the edge's endpoints into account, which is used for reweighting.
+pub(crate) fn dijkstra_until<'a, VId, E, V, F, W, GoalFn, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  mut is_goal: GoalFn,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a VId, &'a VId, &'a E) -> W,
+  W: Weight,
+  GoalFn: FnMut(&VId) -> bool,
+  Vis: Visitor<VId>,
+{
+  let zero = W::default();
+  let mut result = SearchResult {
+    found: None,
+    parents: HashMap::new(),
+    distances: HashMap::new(),
+    explored: 0,
+    status: Status::Exhausted,
+  };
+  result.distances.insert(start, zero);
+  let mut budget = Budget::new(visitor);
+
+  let mut settled = HashSet::new();
+  let mut pruned = HashSet::new();
+  let mut queue = BinaryHeap::new();
+  match visitor.discover_vertex(start, 0) {
+    Control::Break => return result.with_status(Status::Cancelled),
+    Control::Prune => {
+      pruned.insert(start);
+    }
+    Control::Continue => (),
+  }
+  queue.push(Candidate {
+    vid: start,
+    distance: zero,
+    depth: 0,
+  });
+
+  while let Some(Candidate {
+    vid: curr,
+    distance,
+    depth,
+  }) = queue.pop()
+  {
+    // the same vertex can be queued several times, if a shorter path to it was found later
+    if !settled.insert(curr) {
+      continue;
+    }
+    result.explored += 1;
+
+    if is_goal(curr) {
+      result.found = Some((curr, distance));
+      return result.with_status(Status::Found);
+    }
+
+    if pruned.contains(curr) || !budget.allows_depth(depth) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, depth, queue.len()) {
+      return result.with_status(status);
+    }
+
+    for (next, edge) in g.incident_edges(curr).into_iter().flatten() {
+      match visitor.examine_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      let weight = weight_fn(curr, next, edge);
+      debug_assert!(weight >= zero, "dijkstra doesn't support negative weights");
+      let next_distance = distance + weight;
+
+      let improves = !settled.contains(next)
+        && match result.distances.get(next) {
+          None => true,
+          Some(&known_distance) => next_distance < known_distance,
+        };
+      if !improves {
+        if visitor.non_tree_edge(curr, next) == Control::Break {
+          return result.with_status(Status::Cancelled);
+        }
+        continue;
+      }
+
+      match visitor.tree_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      let is_new = result.distances.insert(next, next_distance).is_none();
+      result.parents.insert(next, curr);
+
+      if is_new {
+        match visitor.discover_vertex(next, depth + 1) {
+          Control::Break => return result.with_status(Status::Cancelled),
+          Control::Prune => {
+            pruned.insert(next);
+          }
+          Control::Continue => (),
+        }
+      }
+
+      queue.push(Candidate {
+        vid: next,
+        distance: next_distance,
+        depth: depth + 1,
+      });
+    }
+
+    if visitor.finish_vertex(curr) == Control::Break {
+      return result.with_status(Status::Cancelled);
+    }
+  }
+
+  let status = budget.exhausted();
+  result.with_status(status)
+}
+
+impl<VId, E, V> Graph<VId, E, V>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  /// Finds the shortest path tree for `self`, rooted at vertex with `start_vid`,
+  /// and using `get_edge_weight` to find the weight of the edges.
+  ///
+  /// Uses Dijkstra's algorithm, so the weights must not be negative.
+  ///
+  /// Returns the tree as a graph of references to vertices & edges owned by `self`.
+  pub fn shortest_path_tree<'a, 'b, F, W>(
+    &'a self,
+    start_vid: &'a VId,
+    get_edge_weight: &'b F,
+  ) -> Option<Graph<&'a VId, &'a E, &'a V>>
+  where
+    F: Fn(&'a E) -> W,
+    W: Weight,
+  {
+    let start = self.get_vertex(start_vid)?;
+    let result = dijkstra(self, start_vid, get_edge_weight, None, &mut ());
+
+    let mut tree = Graph::new();
+    tree.push_vertex(start_vid, start);
+
+    for (&to_vid, &from_vid) in result.parents.iter() {
+      if let Some(to) = self.get_vertex(to_vid) {
+        // with parallel edges, the cheapest one is on the shortest path
+        if let Some(edge) = cheapest_edge(self, from_vid, to_vid, get_edge_weight) {
+          tree.push_vertex(to_vid, to);
+          tree.push_edge(from_vid, to_vid, edge);
+        }
+      }
+    }
+
+    Some(tree)
+  }
+}
+
+/// A vertex in the Dijkstra's priority queue, ordered by reversed distance,
+/// since `BinaryHeap` is a max-heap.
+pub(super) struct Candidate<'a, VId, W> {
+  pub(super) vid: &'a VId,
+  pub(super) distance: W,
+  pub(super) depth: usize,
+}
+
+impl<'a, VId, W: Ord> Ord for Candidate<'a, VId, W> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.distance.cmp(&self.distance)
+  }
+}
+
+impl<'a, VId, W: Ord> PartialOrd for Candidate<'a, VId, W> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<'a, VId, W: Ord> PartialEq for Candidate<'a, VId, W> {
+  fn eq(&self, other: &Self) -> bool {
+    self.distance == other.distance
+  }
+}
+
+impl<'a, VId, W: Ord> Eq for Candidate<'a, VId, W> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::Opts;
+
+  /// A -1-> B -1-> C -1-> D, plus shortcuts A -5-> C and B -7-> D,
+  /// and an expensive edge to E, which is otherwise isolated.
+  fn weighted_graph() -> Graph<&'static str, u32> {
+    let mut g = Graph::new();
+    for vid in ["A", "B", "C", "D", "E"] {
+      g.push_vid(vid);
+    }
+    for (from, to, weight) in [
+      ("A", "B", 1),
+      ("B", "C", 1),
+      ("C", "D", 1),
+      ("A", "C", 5),
+      ("B", "D", 7),
+      ("A", "E", 10),
+    ] {
+      g.push_edge(from, to, weight);
+    }
+
+    g
+  }
+
+  #[test]
+  fn dijkstra_test() {
+    let g = weighted_graph();
+
+    let result = dijkstra(&g, &"A", |w| *w, None, &mut ());
+    assert_eq!(result.found, None);
+    assert_eq!(result.explored, 5);
+    assert_eq!(result.distances.get(&"C"), Some(&2));
+    assert_eq!(result.distances.get(&"D"), Some(&3));
+    assert_eq!(result.distances.get(&"E"), Some(&10));
+    assert_eq!(result.parents.get(&"C"), Some(&&"B"));
+    let path = result.path_to(&g, &"D", |w| *w).unwrap();
+    assert_eq!(path.vertices(), [&"A", &"B", &"C", &"D"]);
+    assert_eq!(path.edges(), [&1, &1, &1]);
+    assert_eq!(path.cost(), 3);
+
+    // of the parallel edges, the path takes the cheapest one by `weight_fn`
+    let mut g = g;
+    g.push_edge("C", "D", 4);
+    g.push_edge("C", "D", 0);
+    let result = dijkstra(&g, &"A", |w| *w, None, &mut ());
+    let path = result.path_to(&g, &"D", |w| *w).unwrap();
+    assert_eq!(path.edges(), [&1, &1, &0]);
+    assert_eq!(path.cost(), 2);
+    let g = weighted_graph();
+
+    // stops early once the goal is reached
+    let result = dijkstra(&g, &"A", |w| *w, Some(&"D"), &mut ());
+    assert_eq!(result.found, Some((&"D", 3)));
+    assert_eq!(result.explored, 4);
+    // E was reached, but its distance wasn't finalized
+    assert_eq!(result.distances.get(&"E"), Some(&10));
+
+    // with unit weights it finds the path with the fewest edges
+    let result = dijkstra(&g, &"A", |_| 1, Some(&"D"), &mut ());
+    assert_eq!(result.found, Some((&"D", 2)));
+
+    // the visitor sees the edges that improve distances
+    let mut relaxed = vec![];
+    {
+      let mut opts = Opts {
+        on_explore: Some(Box::new(|from: &&str, to: &&str| relaxed.push((*from, *to)))),
+        ..Opts::default()
+      };
+      dijkstra(&g, &"A", |w| *w, Some(&"D"), &mut opts);
+    }
+    assert_eq!(
+      relaxed,
+      vec![("A", "B"), ("A", "C"), ("A", "E"), ("B", "C"), ("B", "D"), ("C", "D")]
+    );
+  }
+
+  #[test]
+  fn dijkstra_targets_test() {
+    let g = weighted_graph();
+
+    let result = dijkstra_targets(&g, &"A", |w| *w, &["C", "B"], &mut ());
+    assert_eq!(result.found, Some((&"C", 2)));
+    assert_eq!(result.explored, 3);
+
+    let result = dijkstra_targets(&g, &"B", |w| *w, &["C", "E"], &mut ());
+    assert_eq!(result.found, None);
+    assert_eq!(result.distances.get(&"C"), Some(&1));
+
+    // no targets are reached right away
+    let result = dijkstra_targets(&g, &"A", |w| *w, &[], &mut ());
+    assert_eq!(result.found, Some((&"A", 0)));
+    assert_eq!(result.status, Status::Found);
+    assert_eq!(result.explored, 1);
+  }
+
+  #[test]
+  fn shortest_path_tree_test() {
+    let mut g = weighted_graph();
+    // a parallel edge that is cheaper than the original one
+    g.push_edge("A", "E", 4);
+
+    let tree = g.shortest_path_tree(&"A", &(|w| *w)).unwrap();
+    assert_eq!(tree.iter_vertices().count(), 5);
+
+    let mut edges = tree
+      .iter_complete_edges()
+      .map(|(from, to, edge)| (**from, **to, **edge))
+      .collect::<Vec<_>>();
+    edges.sort();
+    assert_eq!(&edges, &[("A", "B", 1), ("A", "E", 4), ("B", "C", 1), ("C", "D", 1)]);
+
+    assert!(g.shortest_path_tree(&"Z", &(|w| *w)).is_none());
+  }
+}
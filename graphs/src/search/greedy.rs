@@ -0,0 +1,353 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+use super::{Budget, Control, SearchResult, Status, Visitor};
+use crate::Graph;
+
+/// Greedy best-first search: always expands the discovered vertex with the lowest `priority_fn`,
+/// e.g. the one estimated to be the closest to the goal. Stops when `is_goal(vertex_id, depth)`
+/// returns `true`.
+///
+/// Unlike `astar`, ignores the cost of reaching a vertex, so it usually finds a goal after exploring
+/// far fewer vertices, but the path to it is not necessarily the shortest one. Each vertex is
+/// discovered once, and its parent is the vertex it was first reached from. Vertices with equal
+/// priorities are expanded in the order they were discovered.
+///
+/// Returns a `SearchResult` like `bfs`, with the distances being depths in the search tree.
+/// `visitor` is notified about the events as in `bfs`, so `Opts` callbacks work the same way.
+pub fn best_first<'a, VId, E, V, GoalFn, P, K, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  is_goal: GoalFn,
+  priority_fn: P,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  GoalFn: Fn(&VId, usize) -> bool,
+  P: Fn(&VId) -> K,
+  K: Ord,
+  Vis: Visitor<VId>,
+{
+  let mut result = SearchResult {
+    found: None,
+    parents: HashMap::new(),
+    distances: HashMap::new(),
+    explored: 0,
+    status: Status::Exhausted,
+  };
+  result.distances.insert(start, 0);
+  let mut budget = Budget::new(visitor);
+
+  let mut pruned = HashSet::new();
+  let mut queue = BinaryHeap::new();
+  let mut seq = 0;
+  match visitor.discover_vertex(start, 0) {
+    Control::Break => return result.with_status(Status::Cancelled),
+    Control::Prune => {
+      pruned.insert(start);
+    }
+    Control::Continue => (),
+  }
+  queue.push(Prioritized {
+    priority: priority_fn(start),
+    seq,
+    vid: start,
+  });
+
+  while let Some(Prioritized { vid: curr, .. }) = queue.pop() {
+    let depth = result.distances[curr];
+    result.explored += 1;
+
+    if is_goal(curr, depth) {
+      result.found = Some((curr, depth));
+      return result.with_status(Status::Found);
+    }
+
+    if pruned.contains(curr) || !budget.allows_depth(depth) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, depth, queue.len()) {
+      return result.with_status(status);
+    }
+
+    for next in g.adjacent(curr) {
+      match visitor.examine_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      if result.distances.contains_key(next) {
+        if visitor.non_tree_edge(curr, next) == Control::Break {
+          return result.with_status(Status::Cancelled);
+        }
+        continue;
+      }
+
+      match visitor.tree_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      result.distances.insert(next, depth + 1);
+      result.parents.insert(next, curr);
+      match visitor.discover_vertex(next, depth + 1) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => {
+          pruned.insert(next);
+        }
+        Control::Continue => (),
+      }
+
+      seq += 1;
+      queue.push(Prioritized {
+        priority: priority_fn(next),
+        seq,
+        vid: next,
+      });
+    }
+
+    if visitor.finish_vertex(curr) == Control::Break {
+      return result.with_status(Status::Cancelled);
+    }
+  }
+
+  let status = budget.exhausted();
+  result.with_status(status)
+}
+
+/// Beam search: a breadth-first search that only keeps the `beam_width` vertices with the lowest
+/// `score_fn` of each level, and drops the rest. Stops when `is_goal(vertex_id, depth)` returns `true`.
+///
+/// Uses memory proportional to `beam_width`, and is fast, but can miss a goal: a dropped vertex can
+/// only be discovered again from a later level. So `Status::Exhausted` means that the beam ran out of
+/// vertices, not that no goal is reachable. With an unlimited `beam_width`, this is `bfs`.
+///
+/// Vertices with equal scores are kept in the order they were reached. Only the kept vertices are
+/// discovered, i.e. get reported to `discover_vertex` and `tree_edge`, and recorded in the result.
+pub fn beam_search<'a, VId, E, V, GoalFn, S, K, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  is_goal: GoalFn,
+  score_fn: S,
+  beam_width: usize,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  GoalFn: Fn(&VId, usize) -> bool,
+  S: Fn(&VId) -> K,
+  K: Ord,
+  Vis: Visitor<VId>,
+{
+  let mut result = SearchResult {
+    found: None,
+    parents: HashMap::new(),
+    distances: HashMap::new(),
+    explored: 0,
+    status: Status::Exhausted,
+  };
+  result.distances.insert(start, 0);
+  let mut budget = Budget::new(visitor);
+
+  // the second element tells if the vertex's edges should be expanded
+  let mut beam = match visitor.discover_vertex(start, 0) {
+    Control::Break => return result.with_status(Status::Cancelled),
+    control => vec![(start, control == Control::Continue)],
+  };
+
+  let mut depth = 0;
+  while !beam.is_empty() {
+    for &(curr, _expand) in &beam {
+      result.explored += 1;
+      if is_goal(curr, depth) {
+        result.found = Some((curr, depth));
+        return result.with_status(Status::Found);
+      }
+    }
+
+    // the vertices reachable from the beam, with the first vertex each of them was reached from
+    let mut candidates = vec![];
+    let mut reached = HashSet::new();
+    for (idx, &(curr, expand)) in beam.iter().enumerate() {
+      if !expand || !budget.allows_depth(depth) {
+        continue;
+      }
+      if let Some(status) = budget.check(visitor, depth, beam.len() - idx - 1 + candidates.len()) {
+        return result.with_status(status);
+      }
+
+      for next in g.adjacent(curr) {
+        match visitor.examine_edge(curr, next) {
+          Control::Break => return result.with_status(Status::Cancelled),
+          Control::Prune => continue,
+          Control::Continue => (),
+        }
+
+        if result.distances.contains_key(next) || !reached.insert(next) {
+          if visitor.non_tree_edge(curr, next) == Control::Break {
+            return result.with_status(Status::Cancelled);
+          }
+          continue;
+        }
+        candidates.push((next, curr));
+      }
+
+      if visitor.finish_vertex(curr) == Control::Break {
+        return result.with_status(Status::Cancelled);
+      }
+    }
+
+    // a stable sort keeps the vertices with equal scores in the order they were reached
+    candidates.sort_by_cached_key(|&(next, _parent)| score_fn(next));
+    beam = vec![];
+    for (next, parent) in candidates {
+      if beam.len() == beam_width {
+        break;
+      }
+
+      match visitor.tree_edge(parent, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      result.distances.insert(next, depth + 1);
+      result.parents.insert(next, parent);
+      match visitor.discover_vertex(next, depth + 1) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        control => beam.push((next, control == Control::Continue)),
+      }
+    }
+    depth += 1;
+  }
+
+  let status = budget.exhausted();
+  result.with_status(status)
+}
+
+/// A vertex waiting in the `best_first` queue, ordered by reversed priority and then by
+/// reversed sequence number, since `BinaryHeap` is a max-heap.
+struct Prioritized<'a, VId, K> {
+  priority: K,
+  seq: usize,
+  vid: &'a VId,
+}
+
+impl<'a, VId, K: Ord> Ord for Prioritized<'a, VId, K> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other
+      .priority
+      .cmp(&self.priority)
+      .then_with(|| other.seq.cmp(&self.seq))
+  }
+}
+
+impl<'a, VId, K: Ord> PartialOrd for Prioritized<'a, VId, K> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<'a, VId, K: Ord> PartialEq for Prioritized<'a, VId, K> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<'a, VId, K: Ord> Eq for Prioritized<'a, VId, K> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::{bfs, record_parents, Opts};
+
+  type Cell = (i32, i32);
+
+  /// An undirected `size` x `size` grid with a wall at column 3, open only at the bottom row.
+  fn walled_grid(size: i32) -> Graph<Cell> {
+    let mut g = Graph::new();
+    let open = |(row, col): Cell| col != 3 || row == size - 1;
+    for row in 0..size {
+      for col in 0..size {
+        g.push_vid((row, col));
+        for next in [(row + 1, col), (row, col + 1)] {
+          if next.0 < size && next.1 < size && open((row, col)) && open(next) {
+            g.push_undirected_edge((row, col), next, ());
+          }
+        }
+      }
+    }
+    g
+  }
+
+  fn distance_to(goal: Cell) -> impl Fn(&Cell) -> i32 {
+    move |&(row, col)| (goal.0 - row).abs() + (goal.1 - col).abs()
+  }
+
+  #[test]
+  fn best_first_test() {
+    let g = walled_grid(8);
+    let (start, goal) = ((7, 0), (7, 7));
+
+    let result = best_first(&g, &start, |vid, _| *vid == goal, distance_to(goal), &mut ());
+    assert_eq!(result.status, Status::Found);
+    assert_eq!(result.found, Some((&goal, 7)));
+    assert_eq!(result.explored, 8);
+    assert!(result.explored < bfs(&g, &start, |vid, _| *vid == goal, &mut ()).explored);
+
+    // the greedy choice leads to the wall first, making the path longer than the shortest one
+    let result = best_first(&g, &(0, 0), |vid, _| *vid == (0, 7), distance_to((0, 7)), &mut ());
+    let path = result.path_to(&g, &(0, 7), |_| ()).unwrap();
+    assert_eq!(path.start(), &&(0, 0));
+    assert!(path.len() >= 21);
+
+    // on_explore sees the same search tree as the result
+    let mut parents = HashMap::new();
+    let mut opts = Opts {
+      on_explore: Some(Box::new(|parent: &Cell, explored: &Cell| {
+        record_parents(&mut parents, parent, explored)
+      })),
+      ..Opts::default()
+    };
+    let result = best_first(&g, &(0, 0), |vid, _| *vid == (0, 7), distance_to((0, 7)), &mut opts);
+    drop(opts);
+    assert_eq!(parents.len(), result.parents.len());
+  }
+
+  #[test]
+  fn beam_search_test() {
+    // S -> A -> C is a dead end, which looks closer to the goal than S -> B -> G
+    let mut g: Graph<&str> = Graph::new();
+    for (from, to) in [("S", "A"), ("S", "B"), ("A", "C"), ("B", "G")] {
+      g.push_edge(from, to, ());
+    }
+    let score = |vid: &&str| match *vid {
+      "A" | "C" => 1,
+      _ => 2,
+    };
+
+    let result = beam_search(&g, &"S", |vid, _| *vid == "G", score, 1, &mut ());
+    assert_eq!(result.found, None);
+    assert_eq!(result.status, Status::Exhausted);
+    assert_eq!(result.distances.len(), 3);
+
+    let result = beam_search(&g, &"S", |vid, _| *vid == "G", score, 2, &mut ());
+    assert_eq!(result.found, Some((&"G", 2)));
+
+    // a beam as wide as the grid keeps the way around the wall, and finds the shortest path
+    let g = walled_grid(8);
+    let (start, goal) = ((0, 0), (0, 7));
+    let shortest = bfs(&g, &start, |vid, _| *vid == goal, &mut ()).found;
+    let result = beam_search(&g, &start, |vid, _| *vid == goal, distance_to(goal), 8, &mut ());
+    assert_eq!(result.found, shortest);
+    // a narrower one still gets around, but not by the shortest path
+    let result = beam_search(&g, &start, |vid, _| *vid == goal, distance_to(goal), 2, &mut ());
+    assert!(result.found.unwrap().1 > shortest.unwrap().1);
+  }
+}
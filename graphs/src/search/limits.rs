@@ -0,0 +1,132 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{Control, Visitor};
+
+/// Limits on the amount of work a search is allowed to do. All limits are optional,
+/// and `Limits::default()` doesn't limit anything.
+///
+/// Searches get the limits from `Visitor::limits`, so they can be set via `Opts::limits`.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+  /// The maximum number of vertices whose edges can be expanded.
+  pub max_expanded: Option<usize>,
+  /// Vertices at this depth (in edges from the start) are still discovered and checked
+  /// against the goal, but their edges aren't expanded.
+  pub max_depth: Option<usize>,
+  /// The search stops once this moment passes.
+  pub deadline: Option<Instant>,
+  /// The search stops once this flag is set, e.g. from another thread.
+  pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// How a search has ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+  /// A goal was reached.
+  Found,
+  /// All reachable vertices were explored without reaching a goal.
+  Exhausted,
+  /// The search hit one of its `Limits`: `max_expanded`, `deadline`, or `max_depth`,
+  /// if some vertices weren't expanded because of it.
+  BudgetExceeded,
+  /// The search was cancelled via `Limits::cancel`, or by the visitor returning `Control::Break`.
+  Cancelled,
+}
+
+/// A search's progress, reported to `Visitor::progress` before expanding each vertex.
+#[derive(Debug, Clone)]
+pub struct Progress {
+  /// The number of expanded vertices, including the one about to be expanded.
+  pub expanded: usize,
+  /// The depth of the vertex about to be expanded.
+  pub depth: usize,
+  /// The number of vertices waiting to be explored, not counting the one about to be expanded.
+  /// Searches using a priority queue count all of its entries, including outdated ones.
+  pub frontier: usize,
+  started: Instant,
+}
+
+impl Progress {
+  /// Time since the search has started.
+  pub fn elapsed(&self) -> Duration {
+    self.started.elapsed()
+  }
+}
+
+/// Keeps track of a search's progress, and checks it against the visitor's limits.
+pub(crate) struct Budget {
+  limits: Limits,
+  progress: Progress,
+  depth_limited: bool,
+}
+
+impl Budget {
+  pub(crate) fn new<VId, Vis>(visitor: &Vis) -> Budget
+  where
+    Vis: Visitor<VId>,
+  {
+    Budget {
+      limits: visitor.limits().cloned().unwrap_or_default(),
+      progress: Progress {
+        expanded: 0,
+        depth: 0,
+        frontier: 0,
+        started: Instant::now(),
+      },
+      depth_limited: false,
+    }
+  }
+
+  /// Tells if a vertex at `depth` can be expanded according to `max_depth`.
+  pub(crate) fn allows_depth(&mut self, depth: usize) -> bool {
+    match self.limits.max_depth {
+      Some(max_depth) if depth >= max_depth => {
+        self.depth_limited = true;
+        false
+      }
+      _ => true,
+    }
+  }
+
+  /// Checks the limits before expanding a vertex at `depth` with `frontier` vertices waiting to be explored,
+  /// and reports the progress to `visitor`. Returns the status the search must stop with, if any.
+  pub(crate) fn check<VId, Vis>(&mut self, visitor: &mut Vis, depth: usize, frontier: usize) -> Option<Status>
+  where
+    Vis: Visitor<VId>,
+  {
+    if let Some(cancel) = &self.limits.cancel {
+      if cancel.load(Ordering::Relaxed) {
+        return Some(Status::Cancelled);
+      }
+    }
+    if let Some(max_expanded) = self.limits.max_expanded {
+      if self.progress.expanded >= max_expanded {
+        return Some(Status::BudgetExceeded);
+      }
+    }
+    if let Some(deadline) = self.limits.deadline {
+      if Instant::now() >= deadline {
+        return Some(Status::BudgetExceeded);
+      }
+    }
+
+    self.progress.expanded += 1;
+    self.progress.depth = depth;
+    self.progress.frontier = frontier;
+    match visitor.progress(&self.progress) {
+      Control::Break => Some(Status::Cancelled),
+      _ => None,
+    }
+  }
+
+  /// The status of a search that ran out of vertices to explore.
+  pub(crate) fn exhausted(&self) -> Status {
+    if self.depth_limited {
+      Status::BudgetExceeded
+    } else {
+      Status::Exhausted
+    }
+  }
+}
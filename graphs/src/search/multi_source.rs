@@ -0,0 +1,162 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+use super::{Budget, Control, Status, Visitor};
+use crate::Graph;
+
+/// Tells which source reached a vertex first in `multi_source_bfs`, and its distance from that source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Claim<'a, VId> {
+  pub source: &'a VId,
+  pub distance: usize,
+}
+
+/// Runs breadth-first search from all of the `sources` at once, so that each reachable vertex
+/// is claimed by its nearest source. This partitions the graph into the areas served by
+/// each source (e.g. nearest facilities), and gives each vertex its distance to the closest one.
+///
+/// When several sources are at the same distance from a vertex, the one that comes first
+/// in `sources` claims it. Repeated sources are ignored.
+///
+/// `visitor` is notified about the events in the same way as in `bfs`, with each source
+/// discovered at depth 0. The returned `Status` is `Exhausted` if all vertices reachable
+/// from the sources were claimed, and tells which limit stopped the search otherwise.
+pub fn multi_source_bfs<'a, VId, E, V, I, Vis>(
+  g: &'a Graph<VId, E, V>,
+  sources: I,
+  visitor: &mut Vis,
+) -> (HashMap<&'a VId, Claim<'a, VId>>, Status)
+where
+  VId: Eq + Hash,
+  V: Hash,
+  I: IntoIterator<Item = &'a VId>,
+  Vis: Visitor<VId>,
+{
+  let mut claims = HashMap::new();
+  let mut budget = Budget::new(visitor);
+
+  // the last element tells if the vertex's edges should be expanded
+  let mut queue = VecDeque::new();
+  for source in sources {
+    if claims.contains_key(source) {
+      continue;
+    }
+
+    claims.insert(source, Claim { source, distance: 0 });
+    match visitor.discover_vertex(source, 0) {
+      Control::Break => return (claims, Status::Cancelled),
+      control => queue.push_back((source, control == Control::Continue)),
+    }
+  }
+
+  while let Some((curr, expand)) = queue.pop_front() {
+    let Claim { source, distance } = claims[curr];
+    if !expand || !budget.allows_depth(distance) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, distance, queue.len()) {
+      return (claims, status);
+    }
+
+    for next in g.adjacent(curr) {
+      match visitor.examine_edge(curr, next) {
+        Control::Break => return (claims, Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      if claims.contains_key(next) {
+        if visitor.non_tree_edge(curr, next) == Control::Break {
+          return (claims, Status::Cancelled);
+        }
+        continue;
+      }
+
+      match visitor.tree_edge(curr, next) {
+        Control::Break => return (claims, Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      claims.insert(
+        next,
+        Claim {
+          source,
+          distance: distance + 1,
+        },
+      );
+      match visitor.discover_vertex(next, distance + 1) {
+        Control::Break => return (claims, Status::Cancelled),
+        control => queue.push_back((next, control == Control::Continue)),
+      }
+    }
+
+    if visitor.finish_vertex(curr) == Control::Break {
+      return (claims, Status::Cancelled);
+    }
+  }
+
+  let status = budget.exhausted();
+  (claims, status)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::{Limits, Opts};
+  use std::sync::atomic::AtomicBool;
+  use std::sync::Arc;
+
+  #[test]
+  fn multi_source_bfs_test() {
+    // A - B - C - D - E - F, and G - H disconnected from the rest
+    let mut g: Graph<&str> = Graph::new();
+    for (from, to) in [("A", "B"), ("B", "C"), ("C", "D"), ("D", "E"), ("E", "F"), ("G", "H")] {
+      g.push_undirected_edge(from, to, ());
+    }
+
+    let (claims, status) = multi_source_bfs(&g, [&"A", &"E", &"A"], &mut ());
+    assert_eq!(status, Status::Exhausted);
+    let claim = |vid| claims.get(&vid).map(|claim| (*claim.source, claim.distance));
+    assert_eq!(claims.len(), 6);
+    assert_eq!(claim("A"), Some(("A", 0)));
+    assert_eq!(claim("B"), Some(("A", 1)));
+    // C is as far from A as from E, and A comes first
+    assert_eq!(claim("C"), Some(("A", 2)));
+    assert_eq!(claim("D"), Some(("E", 1)));
+    assert_eq!(claim("F"), Some(("E", 1)));
+    assert_eq!(claim("G"), None);
+
+    let (claims, _) = multi_source_bfs(&g, [&"E", &"A"], &mut ());
+    assert_eq!(claims[&"C"].source, &"E");
+
+    assert!(multi_source_bfs(&g, [], &mut ()).0.is_empty());
+  }
+
+  #[test]
+  fn multi_source_bfs_limits_test() {
+    let mut g: Graph<u32> = Graph::new();
+    for vid in 0..9 {
+      g.push_undirected_edge(vid, vid + 1, ());
+    }
+
+    let mut opts = Opts {
+      limits: Limits {
+        max_expanded: Some(2),
+        ..Limits::default()
+      },
+      ..Opts::default()
+    };
+    let (claims, status) = multi_source_bfs(&g, [&0, &9], &mut opts);
+    assert_eq!(status, Status::BudgetExceeded);
+    assert_eq!(claims.len(), 4);
+
+    opts.limits = Limits {
+      cancel: Some(Arc::new(AtomicBool::new(true))),
+      ..Limits::default()
+    };
+    let (claims, status) = multi_source_bfs(&g, [&0, &9], &mut opts);
+    assert_eq!(status, Status::Cancelled);
+    assert_eq!(claims.len(), 2);
+  }
+}
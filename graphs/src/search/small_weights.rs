@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use super::{Budget, Control, SearchResult, Status, Visitor};
+use crate::Graph;
+
+/// Finds the shortest paths from `start` in a graph whose edges weigh either 0 or 1,
+/// e.g. free moves and wall breaks. Instead of a heap, uses a deque: vertices reached
+/// by a free move are pushed to its front, and the rest to its back.
+///
+/// Runs in `O(V + E)` time. Otherwise, works like `dijkstra`: the search stops once the
+/// shortest path to `goal` is known, and `visitor` is notified about the same events.
+///
+/// Panics in debug builds if `weight_fn` returns anything other than 0 or 1.
+pub fn zero_one_bfs<'a, VId, E, V, F, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  goal: Option<&VId>,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> usize,
+  Vis: Visitor<VId>,
+{
+  let weight_fn = |edge| {
+    let weight = weight_fn(edge);
+    debug_assert!(weight <= 1, "zero_one_bfs only supports weights 0 and 1");
+    weight
+  };
+
+  bucket_search(g, start, weight_fn, goal, visitor, ZeroOneDeque::default())
+}
+
+/// Finds the shortest paths from `start` using Dial's algorithm: a version of Dijkstra's algorithm
+/// for small non-negative integer weights, which keeps the vertices in buckets by their distance.
+///
+/// Runs in `O(V + E + D)` time, where `D` is the largest distance found, and keeps only
+/// as many buckets as the heaviest edge requires. Otherwise, works like `dijkstra`.
+pub fn dial<'a, VId, E, V, F, Vis>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  goal: Option<&VId>,
+  visitor: &mut Vis,
+) -> SearchResult<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> usize,
+  Vis: Visitor<VId>,
+{
+  bucket_search(g, start, weight_fn, goal, visitor, Buckets::default())
+}
+
+/// A monotone priority queue of vertices with their distances and depths.
+trait BucketQueue<'a, VId> {
+  /// Adds `vid`, reached by an edge of weight `weight`, at `distance` from the start.
+  fn push(&mut self, vid: &'a VId, distance: usize, depth: usize, weight: usize);
+
+  /// Removes a vertex with the smallest distance.
+  fn pop(&mut self) -> Option<(&'a VId, usize, usize)>;
+
+  /// The number of queued vertices.
+  fn len(&self) -> usize;
+}
+
+struct ZeroOneDeque<'a, VId> {
+  deque: VecDeque<(&'a VId, usize, usize)>,
+}
+
+impl<'a, VId> Default for ZeroOneDeque<'a, VId> {
+  fn default() -> Self {
+    ZeroOneDeque { deque: VecDeque::new() }
+  }
+}
+
+impl<'a, VId> BucketQueue<'a, VId> for ZeroOneDeque<'a, VId> {
+  fn push(&mut self, vid: &'a VId, distance: usize, depth: usize, weight: usize) {
+    if weight == 0 {
+      self.deque.push_front((vid, distance, depth));
+    } else {
+      self.deque.push_back((vid, distance, depth));
+    }
+  }
+
+  fn pop(&mut self) -> Option<(&'a VId, usize, usize)> {
+    self.deque.pop_front()
+  }
+
+  fn len(&self) -> usize {
+    self.deque.len()
+  }
+}
+
+/// Circular buckets for Dial's algorithm: the front bucket contains the vertices at distance
+/// `first_distance`, the next one at `first_distance + 1`, and so on.
+struct Buckets<'a, VId> {
+  buckets: VecDeque<Vec<(&'a VId, usize)>>,
+  first_distance: usize,
+  queued: usize,
+}
+
+impl<'a, VId> Default for Buckets<'a, VId> {
+  fn default() -> Self {
+    Buckets {
+      buckets: VecDeque::new(),
+      first_distance: 0,
+      queued: 0,
+    }
+  }
+}
+
+impl<'a, VId> BucketQueue<'a, VId> for Buckets<'a, VId> {
+  fn push(&mut self, vid: &'a VId, distance: usize, depth: usize, _weight: usize) {
+    let idx = distance - self.first_distance;
+    if self.buckets.len() <= idx {
+      self.buckets.resize_with(idx + 1, Vec::new);
+    }
+    self.buckets[idx].push((vid, depth));
+    self.queued += 1;
+  }
+
+  fn pop(&mut self) -> Option<(&'a VId, usize, usize)> {
+    if self.queued == 0 {
+      return None;
+    }
+
+    loop {
+      match self.buckets[0].pop() {
+        Some((vid, depth)) => {
+          self.queued -= 1;
+          return Some((vid, self.first_distance, depth));
+        }
+        None => {
+          // an emptied bucket is reused for the next distance after the last one, keeping its allocation
+          self.buckets.rotate_left(1);
+          self.first_distance += 1;
+        }
+      }
+    }
+  }
+
+  fn len(&self) -> usize {
+    self.queued
+  }
+}
+
+/// Dijkstra's algorithm with integer weights and a pluggable monotone priority queue.
+fn bucket_search<'a, VId, E, V, F, Vis, Q>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+  goal: Option<&VId>,
+  visitor: &mut Vis,
+  mut queue: Q,
+) -> SearchResult<'a, VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> usize,
+  Vis: Visitor<VId>,
+  Q: BucketQueue<'a, VId>,
+{
+  let mut result = SearchResult {
+    found: None,
+    parents: HashMap::new(),
+    distances: HashMap::new(),
+    explored: 0,
+    status: Status::Exhausted,
+  };
+  result.distances.insert(start, 0);
+  let mut budget = Budget::new(visitor);
+
+  let mut settled = HashSet::new();
+  let mut pruned = HashSet::new();
+  match visitor.discover_vertex(start, 0) {
+    Control::Break => return result.with_status(Status::Cancelled),
+    Control::Prune => {
+      pruned.insert(start);
+    }
+    Control::Continue => (),
+  }
+  queue.push(start, 0, 0, 0);
+
+  while let Some((curr, distance, depth)) = queue.pop() {
+    // the same vertex can be queued several times, if a shorter path to it was found later
+    if !settled.insert(curr) {
+      continue;
+    }
+    result.explored += 1;
+
+    if goal == Some(curr) {
+      result.found = Some((curr, distance));
+      return result.with_status(Status::Found);
+    }
+
+    if pruned.contains(curr) || !budget.allows_depth(depth) {
+      continue;
+    }
+    if let Some(status) = budget.check(visitor, depth, queue.len()) {
+      return result.with_status(status);
+    }
+
+    for (next, edge) in g.incident_edges(curr).into_iter().flatten() {
+      match visitor.examine_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      let weight = weight_fn(edge);
+      let next_distance = distance + weight;
+
+      let improves = !settled.contains(next)
+        && match result.distances.get(next) {
+          None => true,
+          Some(&known_distance) => next_distance < known_distance,
+        };
+      if !improves {
+        if visitor.non_tree_edge(curr, next) == Control::Break {
+          return result.with_status(Status::Cancelled);
+        }
+        continue;
+      }
+
+      match visitor.tree_edge(curr, next) {
+        Control::Break => return result.with_status(Status::Cancelled),
+        Control::Prune => continue,
+        Control::Continue => (),
+      }
+
+      let is_new = result.distances.insert(next, next_distance).is_none();
+      result.parents.insert(next, curr);
+
+      if is_new {
+        match visitor.discover_vertex(next, depth + 1) {
+          Control::Break => return result.with_status(Status::Cancelled),
+          Control::Prune => {
+            pruned.insert(next);
+          }
+          Control::Continue => (),
+        }
+      }
+
+      queue.push(next, next_distance, depth + 1, weight);
+    }
+
+    if visitor.finish_vertex(curr) == Control::Break {
+      return result.with_status(Status::Cancelled);
+    }
+  }
+
+  let status = budget.exhausted();
+  result.with_status(status)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::dijkstra;
+
+  /// A grid of `(row, col)` cells, where moving into a cell marked with `#` costs `wall`,
+  /// and moving into any other cell costs its digit, or 0 for `.`.
+  fn grid(rows: &[&str], wall: usize) -> Graph<(usize, usize), usize> {
+    let mut g = Graph::new();
+    let cost = |row: usize, col: usize| match rows[row].as_bytes()[col] {
+      b'#' => wall,
+      b'.' => 0,
+      digit => (digit - b'0') as usize,
+    };
+
+    for row in 0..rows.len() {
+      for col in 0..rows[row].len() {
+        g.push_vid((row, col));
+        if col + 1 < rows[row].len() {
+          g.push_edge((row, col), (row, col + 1), cost(row, col + 1));
+          g.push_edge((row, col + 1), (row, col), cost(row, col));
+        }
+        if row + 1 < rows.len() {
+          g.push_edge((row, col), (row + 1, col), cost(row + 1, col));
+          g.push_edge((row + 1, col), (row, col), cost(row, col));
+        }
+      }
+    }
+
+    g
+  }
+
+  #[test]
+  fn zero_one_bfs_test() {
+    let g = grid(&["..#..", "###.#", "..#..", ".###.", "....."], 1);
+
+    // the fewest walls to break on the way from the top left corner to the bottom right one
+    let result = zero_one_bfs(&g, &(0, 0), |w| *w, Some(&(4, 4)), &mut ());
+    assert_eq!(result.found, Some((&(4, 4), 1)));
+
+    let all = zero_one_bfs(&g, &(0, 0), |w| *w, None, &mut ());
+    let expected = dijkstra(&g, &(0, 0), |w| *w, None, &mut ());
+    assert_eq!(all.distances, expected.distances);
+    assert_eq!(all.explored, 25);
+  }
+
+  #[test]
+  fn dial_test() {
+    let g = grid(&["13#5.", "9#2#1", "..7.4", "#3#9.", "2.8.6"], 5);
+
+    let result = dial(&g, &(0, 0), |w| *w, Some(&(4, 4)), &mut ());
+    let expected = dijkstra(&g, &(0, 0), |w| *w, Some(&(4, 4)), &mut ());
+    assert_eq!(result.found, expected.found);
+
+    for start in [(0, 0), (2, 2), (4, 4)] {
+      let all = dial(&g, &start, |w| *w, None, &mut ());
+      let expected = dijkstra(&g, &start, |w| *w, None, &mut ());
+      assert_eq!(all.distances, expected.distances);
+
+      let path = all.path_to(&g, &(0, 4), |w| *w).unwrap();
+      let weight = path.edges().iter().copied().sum::<usize>();
+      assert_eq!(weight, path.cost());
+    }
+  }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::{Control, Progress, Visitor};
+
+/// Statistics of a single search run, useful to compare how different searches
+/// behave on the same graph.
+///
+/// `SearchStats` is a visitor, so it can be passed to any search directly, or set as `Opts::stats`
+/// to be collected alongside the callbacks. It can be printed, and, with the `serde` feature,
+/// serialized.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchStats {
+  /// The number of vertices whose edges were expanded.
+  pub expanded: usize,
+  /// The number of edges examined while expanding the vertices.
+  pub edges_examined: usize,
+  /// The largest number of vertices waiting to be explored at once.
+  pub max_frontier: usize,
+  /// The largest depth (in edges from the start) at which a vertex was discovered.
+  pub max_depth: usize,
+  /// Time from the start of the search until the last vertex was expanded.
+  pub elapsed: Duration,
+  /// The number of vertices discovered at each depth, i.e. the sizes of the breadth-first levels.
+  pub frontier_sizes: Vec<usize>,
+}
+
+impl<VId> Visitor<VId> for SearchStats {
+  fn discover_vertex(&mut self, _vid: &VId, depth: usize) -> Control {
+    if self.frontier_sizes.len() <= depth {
+      self.frontier_sizes.resize(depth + 1, 0);
+    }
+    self.frontier_sizes[depth] += 1;
+    self.max_depth = self.max_depth.max(depth);
+
+    Control::Continue
+  }
+
+  fn examine_edge(&mut self, _from: &VId, _to: &VId) -> Control {
+    self.edges_examined += 1;
+    Control::Continue
+  }
+
+  fn progress(&mut self, progress: &Progress) -> Control {
+    self.expanded = progress.expanded;
+    self.max_frontier = self.max_frontier.max(progress.frontier);
+    self.max_depth = self.max_depth.max(progress.depth);
+    self.elapsed = progress.elapsed();
+
+    Control::Continue
+  }
+}
+
+impl fmt::Display for SearchStats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(f, "expanded:       {}", self.expanded)?;
+    writeln!(f, "edges examined: {}", self.edges_examined)?;
+    writeln!(f, "max frontier:   {}", self.max_frontier)?;
+    writeln!(f, "max depth:      {}", self.max_depth)?;
+    writeln!(f, "elapsed:        {:?}", self.elapsed)?;
+    write!(f, "frontier sizes: {:?}", self.frontier_sizes)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::search::{astar, bfs, dijkstra, AStarOpts, Opts};
+  use crate::Graph;
+
+  /// A 5x5 grid of `(row, col)` cells, connected to their neighbours by edges of weight 1.
+  fn grid() -> Graph<(i32, i32), u32> {
+    let mut g = Graph::new();
+    for row in 0..5 {
+      for col in 0..5 {
+        g.push_vid((row, col));
+        if col < 4 {
+          g.push_undirected_edge((row, col), (row, col + 1), 1);
+        }
+        if row < 4 {
+          g.push_undirected_edge((row, col), (row + 1, col), 1);
+        }
+      }
+    }
+    g
+  }
+
+  #[test]
+  fn search_stats_test() {
+    let g = grid();
+    let start = (0, 0);
+
+    let mut bfs_stats = SearchStats::default();
+    bfs(&g, &start, |_, _| false, &mut bfs_stats);
+    assert_eq!(bfs_stats.expanded, 25);
+    assert_eq!(bfs_stats.edges_examined, 80);
+    assert_eq!(bfs_stats.max_depth, 8);
+    assert_eq!(bfs_stats.max_frontier, 4);
+    assert_eq!(bfs_stats.frontier_sizes, vec![1, 2, 3, 4, 5, 4, 3, 2, 1]);
+
+    let mut dijkstra_stats = SearchStats::default();
+    dijkstra(&g, &start, |w| *w, Some(&(4, 4)), &mut dijkstra_stats);
+    assert_eq!(dijkstra_stats.frontier_sizes, bfs_stats.frontier_sizes);
+
+    // a perfect heuristic only expands the vertices on the shortest paths
+    let manhattan = |&(row, col): &(i32, i32)| (4 - row + 4 - col) as u32;
+    let mut astar_stats = SearchStats::default();
+    let path = astar(
+      &g,
+      &start,
+      |vid| *vid == (4, 4),
+      |w| *w,
+      manhattan,
+      AStarOpts::default(),
+      &mut astar_stats,
+    )
+    .0
+    .unwrap();
+    assert_eq!(path.cost(), 8);
+    assert!(astar_stats.expanded < dijkstra_stats.expanded);
+
+    // the same stats are collected via `Opts`
+    let mut opts = Opts {
+      stats: Some(SearchStats::default()),
+      ..Opts::default()
+    };
+    bfs(&g, &start, |_, _| false, &mut opts);
+    let stats = opts.stats.unwrap();
+    assert_eq!(stats.frontier_sizes, bfs_stats.frontier_sizes);
+    assert_eq!(stats.edges_examined, bfs_stats.edges_examined);
+    assert!(stats.to_string().starts_with("expanded:       25\n"));
+  }
+}
@@ -0,0 +1,103 @@
+use super::{Limits, Progress};
+
+/// Tells a traversal how to proceed after calling one of the `Visitor` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Control {
+  /// Proceed as usual.
+  #[default]
+  Continue,
+  /// Skip the current edge (when returned from an edge callback), or don't expand
+  /// the current vertex's edges (when returned from `discover_vertex`).
+  /// A pruned vertex is still checked against the goal.
+  Prune,
+  /// Stop the traversal immediately.
+  Break,
+}
+
+/// Hooks into the events of a graph traversal.
+///
+/// All methods have default no-op implementations that return `Control::Continue`,
+/// so implementors only need to override the events they are interested in.
+/// Since traversals accept visitors generically, the calls are statically dispatched,
+/// and the visitor can freely borrow from its environment.
+///
+/// `()` is a visitor that does nothing, and `Opts` adapts callback closures into a visitor.
+pub trait Visitor<VId> {
+  /// Called when `vid` is seen for the first time, at the given `depth`.
+  /// It's also called for the start vertex with `depth == 0`.
+  fn discover_vertex(&mut self, _vid: &VId, _depth: usize) -> Control {
+    Control::Continue
+  }
+
+  /// Called for each edge going out of a vertex that is being expanded,
+  /// before checking if `to` was already discovered.
+  fn examine_edge(&mut self, _from: &VId, _to: &VId) -> Control {
+    Control::Continue
+  }
+
+  /// Called for an examined edge leading to an undiscovered vertex `to`,
+  /// i.e. an edge that becomes part of the search tree.
+  fn tree_edge(&mut self, _from: &VId, _to: &VId) -> Control {
+    Control::Continue
+  }
+
+  /// Called for an examined edge leading to an already discovered vertex `to`.
+  fn non_tree_edge(&mut self, _from: &VId, _to: &VId) -> Control {
+    Control::Continue
+  }
+
+  /// Called after all edges going out of `vid` were examined.
+  fn finish_vertex(&mut self, _vid: &VId) -> Control {
+    Control::Continue
+  }
+
+  /// Limits the search has to respect. Called once, when the search starts.
+  fn limits(&self) -> Option<&Limits> {
+    None
+  }
+
+  /// Called before expanding each vertex with the progress of the search so far.
+  /// Returning `Control::Break` cancels the search; `Control::Prune` is treated as `Control::Continue`.
+  fn progress(&mut self, _progress: &Progress) -> Control {
+    Control::Continue
+  }
+}
+
+impl<VId> Visitor<VId> for () {}
+
+/// Adapts a `Visitor<VId>` to searches over `&VId` nodes, which is how
+/// the implicit-graph searches see vertices of a `Graph`.
+pub(crate) struct ByRef<'v, Vis>(pub(crate) &'v mut Vis);
+
+impl<'v, 'a, VId, Vis> Visitor<&'a VId> for ByRef<'v, Vis>
+where
+  Vis: Visitor<VId>,
+{
+  fn discover_vertex(&mut self, vid: &&'a VId, depth: usize) -> Control {
+    self.0.discover_vertex(vid, depth)
+  }
+
+  fn examine_edge(&mut self, from: &&'a VId, to: &&'a VId) -> Control {
+    self.0.examine_edge(from, to)
+  }
+
+  fn tree_edge(&mut self, from: &&'a VId, to: &&'a VId) -> Control {
+    self.0.tree_edge(from, to)
+  }
+
+  fn non_tree_edge(&mut self, from: &&'a VId, to: &&'a VId) -> Control {
+    self.0.non_tree_edge(from, to)
+  }
+
+  fn finish_vertex(&mut self, vid: &&'a VId) -> Control {
+    self.0.finish_vertex(vid)
+  }
+
+  fn limits(&self) -> Option<&Limits> {
+    self.0.limits()
+  }
+
+  fn progress(&mut self, progress: &Progress) -> Control {
+    self.0.progress(progress)
+  }
+}
@@ -0,0 +1,333 @@
+use std::{
+  collections::{HashSet, VecDeque},
+  hash::Hash,
+};
+
+use crate::Graph;
+
+/// A step-by-step traversal that only borrows the graph during each `next` call.
+///
+/// This allows interleaving a traversal with other work (including mutating the graph
+/// between the steps), or stopping it at any point. Use `iter` to get a normal `Iterator`
+/// over `(vertex_id, depth)` pairs; since `&mut W` is a `Walker` too, `(&mut walker).iter(&g)`
+/// allows to continue the traversal after the iterator is dropped.
+pub trait Walker<VId, E, V> {
+  /// Returns the next vertex id and its depth, or `None` if the traversal is exhausted.
+  fn next(&mut self, g: &Graph<VId, E, V>) -> Option<(VId, usize)>;
+
+  fn iter(self, g: &Graph<VId, E, V>) -> WalkIter<'_, Self, VId, E, V>
+  where
+    Self: Sized,
+  {
+    WalkIter { walker: self, g }
+  }
+}
+
+impl<W, VId, E, V> Walker<VId, E, V> for &mut W
+where
+  W: Walker<VId, E, V>,
+{
+  fn next(&mut self, g: &Graph<VId, E, V>) -> Option<(VId, usize)> {
+    (**self).next(g)
+  }
+}
+
+/// An `Iterator` over a `Walker` and the graph it traverses, returned by `Walker::iter`.
+pub struct WalkIter<'g, W, VId, E, V> {
+  walker: W,
+  g: &'g Graph<VId, E, V>,
+}
+
+impl<'g, W, VId, E, V> WalkIter<'g, W, VId, E, V> {
+  pub fn into_walker(self) -> W {
+    self.walker
+  }
+}
+
+impl<'g, W, VId, E, V> Iterator for WalkIter<'g, W, VId, E, V>
+where
+  W: Walker<VId, E, V>,
+{
+  type Item = (VId, usize);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.walker.next(self.g)
+  }
+}
+
+/// Breadth-first walker. Yields each reachable vertex once, in the order of increasing depth
+/// as long as all seeds are added before the walk starts. A seed added later is only walked after
+/// the vertices already in the queue, so the order of increasing depth holds per seed.
+#[derive(Debug, Clone)]
+pub struct Bfs<VId> {
+  queue: VecDeque<(VId, usize)>,
+  discovered: HashSet<VId>,
+}
+
+impl<VId> Bfs<VId>
+where
+  VId: Eq + Hash + Clone,
+{
+  pub fn new(start: VId) -> Bfs<VId> {
+    let mut bfs = Bfs {
+      queue: VecDeque::new(),
+      discovered: HashSet::new(),
+    };
+    bfs.seed(start);
+    bfs
+  }
+
+  /// Adds `vid` to the back of the queue with depth 0, unless it was already discovered.
+  /// Returns `true` if `vid` was added.
+  pub fn seed(&mut self, vid: VId) -> bool {
+    let added = self.discovered.insert(vid.clone());
+    if added {
+      self.queue.push_back((vid, 0));
+    }
+    added
+  }
+
+  /// Vertex ids (and their depths) discovered, but not yet yielded.
+  pub fn frontier(&self) -> &VecDeque<(VId, usize)> {
+    &self.queue
+  }
+
+  pub fn is_discovered(&self, vid: &VId) -> bool {
+    self.discovered.contains(vid)
+  }
+}
+
+impl<VId, E, V> Walker<VId, E, V> for Bfs<VId>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  fn next(&mut self, g: &Graph<VId, E, V>) -> Option<(VId, usize)> {
+    let (curr, depth) = self.queue.pop_front()?;
+
+    for next in g.adjacent(&curr) {
+      if !self.discovered.contains(next) {
+        self.discovered.insert(next.clone());
+        self.queue.push_back((next.clone(), depth + 1));
+      }
+    }
+
+    Some((curr, depth))
+  }
+}
+
+/// Depth-first walker. Yields each reachable vertex once, in pre-order: a vertex is yielded
+/// before any of its descendants, and its adjacent vertices are visited in the order of its edges.
+///
+/// `depth` is the depth of the vertex in the DFS tree.
+#[derive(Debug, Clone)]
+pub struct Dfs<VId> {
+  stack: Vec<(VId, usize)>,
+  discovered: HashSet<VId>,
+}
+
+impl<VId> Dfs<VId>
+where
+  VId: Eq + Hash + Clone,
+{
+  pub fn new(start: VId) -> Dfs<VId> {
+    let mut dfs = Dfs {
+      stack: vec![],
+      discovered: HashSet::new(),
+    };
+    dfs.seed(start);
+    dfs
+  }
+
+  /// Pushes `vid` on top of the stack with depth 0, unless it was already discovered,
+  /// so it will be visited next. Returns `true` if `vid` was added.
+  pub fn seed(&mut self, vid: VId) -> bool {
+    let added = !self.discovered.contains(&vid);
+    if added {
+      self.stack.push((vid, 0));
+    }
+    added
+  }
+
+  /// Vertex ids (and their depths) waiting to be visited, the top of the stack is the last one.
+  /// Can contain already discovered vertex ids, which will be skipped.
+  pub fn frontier(&self) -> &[(VId, usize)] {
+    &self.stack
+  }
+
+  pub fn is_discovered(&self, vid: &VId) -> bool {
+    self.discovered.contains(vid)
+  }
+}
+
+impl<VId, E, V> Walker<VId, E, V> for Dfs<VId>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  fn next(&mut self, g: &Graph<VId, E, V>) -> Option<(VId, usize)> {
+    while let Some((curr, depth)) = self.stack.pop() {
+      if self.discovered.insert(curr.clone()) {
+        // pushing in reverse, so that the first edge is on top of the stack
+        for next in g.adjacent(&curr).into_iter().rev() {
+          if !self.discovered.contains(next) {
+            self.stack.push((next.clone(), depth + 1));
+          }
+        }
+
+        return Some((curr, depth));
+      }
+    }
+
+    None
+  }
+}
+
+/// Depth-first walker yielding vertices in post-order: a vertex is yielded only after
+/// all of its descendants. Reversed post-order of a DAG is a topological order.
+#[derive(Debug, Clone)]
+pub struct DfsPostOrder<VId> {
+  stack: Vec<(VId, usize)>,
+  discovered: HashSet<VId>,
+  finished: HashSet<VId>,
+}
+
+impl<VId> DfsPostOrder<VId>
+where
+  VId: Eq + Hash + Clone,
+{
+  pub fn new(start: VId) -> DfsPostOrder<VId> {
+    let mut dfs = DfsPostOrder {
+      stack: vec![],
+      discovered: HashSet::new(),
+      finished: HashSet::new(),
+    };
+    dfs.seed(start);
+    dfs
+  }
+
+  /// Pushes `vid` on top of the stack with depth 0, unless it was already discovered.
+  /// Returns `true` if `vid` was added.
+  pub fn seed(&mut self, vid: VId) -> bool {
+    let added = !self.discovered.contains(&vid);
+    if added {
+      self.stack.push((vid, 0));
+    }
+    added
+  }
+
+  /// Vertex ids (and their depths) that are either waiting to be visited,
+  /// or waiting for their descendants to finish. The top of the stack is the last one.
+  pub fn frontier(&self) -> &[(VId, usize)] {
+    &self.stack
+  }
+
+  pub fn is_discovered(&self, vid: &VId) -> bool {
+    self.discovered.contains(vid)
+  }
+
+  pub fn is_finished(&self, vid: &VId) -> bool {
+    self.finished.contains(vid)
+  }
+}
+
+impl<VId, E, V> Walker<VId, E, V> for DfsPostOrder<VId>
+where
+  VId: Eq + Hash + Clone,
+  V: Hash,
+{
+  fn next(&mut self, g: &Graph<VId, E, V>) -> Option<(VId, usize)> {
+    while let Some((curr, depth)) = self.stack.last().cloned() {
+      if self.discovered.insert(curr.clone()) {
+        for next in g.adjacent(&curr).into_iter().rev() {
+          if !self.discovered.contains(next) {
+            self.stack.push((next.clone(), depth + 1));
+          }
+        }
+      } else {
+        self.stack.pop();
+
+        // the same vertex id can be pushed several times before it's discovered
+        if self.finished.insert(curr.clone()) {
+          return Some((curr, depth));
+        }
+      }
+    }
+
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn slide_graph() -> Graph<&'static str> {
+    let mut g = Graph::new();
+    for vid in ["A", "B", "C", "D", "E", "F"] {
+      g.push_vid(vid);
+    }
+    for (from, to) in [
+      ("A", "B"),
+      ("A", "C"),
+      ("A", "D"),
+      ("B", "F"),
+      ("C", "D"),
+      ("D", "E"),
+      ("E", "A"),
+    ] {
+      g.push_edge(from, to, ());
+    }
+
+    g
+  }
+
+  #[test]
+  fn bfs_walker_test() {
+    let g = slide_graph();
+
+    let order = Bfs::new("A").iter(&g).collect::<Vec<_>>();
+    assert_eq!(order, vec![("A", 0), ("B", 1), ("C", 1), ("D", 1), ("F", 2), ("E", 2)]);
+
+    // can be stopped, inspected, and continued
+    let mut bfs = Bfs::new("A");
+    assert_eq!((&mut bfs).iter(&g).take(2).count(), 2);
+    assert_eq!(
+      bfs.frontier().iter().collect::<Vec<_>>(),
+      vec![&("C", 1), &("D", 1), &("F", 2)]
+    );
+    assert!(bfs.is_discovered(&"F"));
+    assert!(!bfs.is_discovered(&"E"));
+    assert_eq!(bfs.next(&g), Some(("C", 1)));
+
+    // re-seeding with an unreachable vertex
+    let mut g = g;
+    g.push_vid("Z");
+    g.push_edge("Z", "Y", ());
+    let mut bfs = Bfs::new("A");
+    assert!(!bfs.seed("A"));
+    assert!(bfs.seed("Z"));
+    let order = bfs.iter(&g).map(|(vid, _)| vid).collect::<Vec<_>>();
+    assert_eq!(order, vec!["A", "Z", "B", "C", "D", "Y", "F", "E"]);
+  }
+
+  #[test]
+  fn dfs_walkers_test() {
+    let g = slide_graph();
+
+    let order = Dfs::new("A").iter(&g).collect::<Vec<_>>();
+    assert_eq!(order, vec![("A", 0), ("B", 1), ("F", 2), ("C", 1), ("D", 2), ("E", 3)]);
+
+    let order = DfsPostOrder::new("A").iter(&g).collect::<Vec<_>>();
+    assert_eq!(order, vec![("F", 2), ("B", 1), ("E", 3), ("D", 2), ("C", 1), ("A", 0)]);
+
+    let mut dfs = DfsPostOrder::new("C");
+    assert_eq!(dfs.next(&g), Some(("F", 5)));
+    assert!(dfs.is_finished(&"F"));
+    assert!(!dfs.is_finished(&"B"));
+    assert_eq!(
+      dfs.frontier().iter().map(|(vid, _)| *vid).collect::<Vec<_>>(),
+      vec!["C", "D", "E", "A", "B"]
+    );
+  }
+}
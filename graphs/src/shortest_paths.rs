@@ -0,0 +1,66 @@
+//! Shortest paths algorithms that go beyond a single search: negative weights, all pairs,
+//! and alternatives to the shortest path.
+//! Single-source searches with non-negative weights, such as `dijkstra` and `astar`, are in `search`.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::search::{path_through, trace_parents};
+use crate::{Graph, Path};
+
+mod all_pairs;
+mod bellman_ford;
+mod yen;
+pub use all_pairs::{floyd_warshall, johnson, DistanceMatrix};
+pub use bellman_ford::{bellman_ford, spfa};
+pub use yen::{k_shortest_paths, KShortestPaths};
+
+/// Shortest paths from a single start vertex to all vertices reachable from it.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<'a, VId, W> {
+  /// Maps each reachable vertex id (including the start) to the weight of the shortest path to it.
+  pub distances: HashMap<&'a VId, W>,
+  /// Maps each reachable vertex id to its predecessor on the shortest path. The start has no parent.
+  pub parents: HashMap<&'a VId, &'a VId>,
+}
+
+impl<'a, VId, W> ShortestPaths<'a, VId, W>
+where
+  VId: Eq + Hash,
+{
+  /// Reconstructs the shortest path from the start vertex to `vid` through the edges of `g`,
+  /// the graph that was searched, with its weight as the cost. Returns `None` if `vid` isn't reachable.
+  ///
+  /// Of any parallel edges, the path takes the cheapest one according to `weight_fn`.
+  pub fn path_to<E, V, F>(&self, g: &'a Graph<VId, E, V>, vid: &VId, weight_fn: F) -> Option<Path<&'a VId, &'a E, W>>
+  where
+    V: Hash,
+    W: Ord + Copy,
+    F: Fn(&'a E) -> W,
+  {
+    let (&vid, &distance) = self.distances.get_key_value(vid)?;
+    Some(path_through(g, trace_parents(&self.parents, vid), weight_fn, distance))
+  }
+}
+
+/// Returned when shortest paths are undefined, because a cycle with negative total weight
+/// is reachable: going around it again always gives a cheaper path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle<VId> {
+  /// Vertex ids along the cycle, in the order of its edges. The edge from the last vertex
+  /// back to the first one closes the cycle.
+  pub vertices: Vec<VId>,
+}
+
+impl<VId: fmt::Debug> fmt::Display for NegativeCycle<VId> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "negative cycle:")?;
+    for vid in self.vertices.iter().chain(self.vertices.first()) {
+      write!(f, " {:?}", vid)?;
+    }
+    Ok(())
+  }
+}
+
+impl<VId: fmt::Debug> Error for NegativeCycle<VId> {}
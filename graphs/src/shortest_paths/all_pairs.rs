@@ -0,0 +1,310 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::{Index, Sub};
+
+use super::bellman_ford::relax_edges;
+use super::{NegativeCycle, ShortestPaths};
+use crate::search::{dijkstra_until, path_through, Weight};
+use crate::{Graph, Path};
+
+/// Shortest path distances between all pairs of vertices, as computed by `floyd_warshall` or `johnson`.
+///
+/// Can be indexed by a pair of vertex ids: `matrix[(&from, &to)]` is `None` if `to` is unreachable
+/// from `from`, and panics if any of them is not a vertex of the graph.
+#[derive(Debug, Clone)]
+pub struct DistanceMatrix<'a, VId, W> {
+  vids: Vec<&'a VId>,
+  indices: HashMap<&'a VId, usize>,
+  // `vids.len() * vids.len()` matrices in row-major order
+  distances: Vec<Option<W>>,
+  // index of the vertex preceding the column's vertex on the shortest path from the row's vertex
+  predecessors: Vec<Option<usize>>,
+}
+
+impl<'a, VId, W> DistanceMatrix<'a, VId, W>
+where
+  VId: Eq + Hash,
+  W: Weight,
+{
+  fn new(vids: Vec<&'a VId>) -> DistanceMatrix<'a, VId, W> {
+    let n = vids.len();
+    let indices = vids.iter().enumerate().map(|(idx, &vid)| (vid, idx)).collect();
+
+    DistanceMatrix {
+      vids,
+      indices,
+      distances: vec![None; n * n],
+      predecessors: vec![None; n * n],
+    }
+  }
+
+  /// All vertex ids of the graph, in the order of the matrix rows & columns.
+  pub fn vertices(&self) -> &[&'a VId] {
+    &self.vids
+  }
+
+  /// Weight of the shortest path from `from` to `to`, or `None` if it doesn't exist.
+  pub fn distance(&self, from: &VId, to: &VId) -> Option<W> {
+    let idx = self.cell(from, to)?;
+    self.distances[idx]
+  }
+
+  /// Reconstructs the shortest path from `from` to `to` through the edges of `g`, the graph the matrix
+  /// was computed for, with its weight as the cost. Returns `None` if `to` is not reachable from `from`.
+  ///
+  /// Of any parallel edges, the path takes the cheapest one according to `weight_fn`.
+  pub fn path<E, V, F>(
+    &self,
+    g: &'a Graph<VId, E, V>,
+    from: &VId,
+    to: &VId,
+    weight_fn: F,
+  ) -> Option<Path<&'a VId, &'a E, W>>
+  where
+    V: Hash,
+    F: Fn(&'a E) -> W,
+  {
+    let (&from_idx, &to_idx) = (self.indices.get(from)?, self.indices.get(to)?);
+    let distance = self.distances[self.at(from_idx, to_idx)]?;
+
+    let mut path = vec![self.vids[to_idx]];
+    let mut curr = to_idx;
+    while curr != from_idx {
+      curr = self.predecessors[self.at(from_idx, curr)]?;
+      path.push(self.vids[curr]);
+    }
+
+    path.reverse();
+    Some(path_through(g, path, weight_fn, distance))
+  }
+
+  fn cell(&self, from: &VId, to: &VId) -> Option<usize> {
+    Some(self.at(*self.indices.get(from)?, *self.indices.get(to)?))
+  }
+
+  fn at(&self, row: usize, col: usize) -> usize {
+    row * self.vids.len() + col
+  }
+
+  /// Walks the predecessors of the `row` vertex's shortest paths back from `row`
+  /// until a vertex repeats, which happens when `row` is on a negative cycle.
+  fn negative_cycle(&self, row: usize) -> NegativeCycle<&'a VId> {
+    let mut seen = HashSet::new();
+    let mut curr = row;
+    while seen.insert(curr) {
+      curr = self.predecessors[self.at(row, curr)].expect("vertex on a negative cycle must have a predecessor");
+    }
+
+    let on_cycle = curr;
+    let mut vertices = vec![self.vids[on_cycle]];
+    curr = self.predecessors[self.at(row, on_cycle)].unwrap();
+    while curr != on_cycle {
+      vertices.push(self.vids[curr]);
+      curr = self.predecessors[self.at(row, curr)].unwrap();
+    }
+
+    vertices.reverse();
+    NegativeCycle { vertices }
+  }
+}
+
+impl<'a, 'b, VId, W> Index<(&'b VId, &'b VId)> for DistanceMatrix<'a, VId, W>
+where
+  VId: Eq + Hash,
+  W: Weight,
+{
+  type Output = Option<W>;
+
+  fn index(&self, (from, to): (&'b VId, &'b VId)) -> &Self::Output {
+    let idx = self.cell(from, to).expect("vertex id is not in the distance matrix");
+    &self.distances[idx]
+  }
+}
+
+/// Finds shortest paths between all pairs of vertices using the Floyd-Warshall algorithm,
+/// with `weight_fn` giving the weight of each edge. Weights can be negative.
+///
+/// Runs in `O(V^3)` time and uses `O(V^2)` memory, which is a good fit for dense graphs.
+/// For sparse graphs, `johnson` is usually faster.
+///
+/// Returns a `NegativeCycle` error if the graph contains a cycle with negative total weight.
+pub fn floyd_warshall<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  weight_fn: F,
+) -> Result<DistanceMatrix<'a, VId, W>, NegativeCycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let zero = W::default();
+  let mut matrix = DistanceMatrix::new(g.all_vids());
+  let n = matrix.vids.len();
+
+  for idx in 0..n {
+    let diagonal = matrix.at(idx, idx);
+    matrix.distances[diagonal] = Some(zero);
+  }
+
+  for (from, to, edge) in g.iter_complete_edges() {
+    let weight = weight_fn(edge);
+    let (from_idx, to_idx) = (matrix.indices[from], matrix.indices[to]);
+    let cell = matrix.at(from_idx, to_idx);
+
+    // with parallel edges, only the cheapest one matters
+    let improves = match matrix.distances[cell] {
+      None => true,
+      Some(known) => weight < known,
+    };
+    if improves {
+      matrix.distances[cell] = Some(weight);
+      matrix.predecessors[cell] = Some(from_idx);
+    }
+  }
+
+  for k in 0..n {
+    for i in 0..n {
+      let through_k = match matrix.distances[matrix.at(i, k)] {
+        None => continue,
+        Some(distance) => distance,
+      };
+
+      for j in 0..n {
+        if let Some(k_to_j) = matrix.distances[matrix.at(k, j)] {
+          let distance = through_k + k_to_j;
+          let cell = matrix.at(i, j);
+
+          let improves = match matrix.distances[cell] {
+            None => true,
+            Some(known) => distance < known,
+          };
+          if improves {
+            matrix.distances[cell] = Some(distance);
+            matrix.predecessors[cell] = matrix.predecessors[matrix.at(k, j)];
+          }
+        }
+      }
+
+      // stopping right away, since distances around a negative cycle keep decreasing
+      if matches!(matrix.distances[matrix.at(i, i)], Some(distance) if distance < zero) {
+        return Err(matrix.negative_cycle(i));
+      }
+    }
+  }
+
+  Ok(matrix)
+}
+
+/// Finds shortest paths between all pairs of vertices using Johnson's algorithm,
+/// with `weight_fn` giving the weight of each edge. Weights can be negative.
+///
+/// The edges are reweighted to be non-negative using potentials found by Bellman-Ford,
+/// which allows running Dijkstra from each vertex. Runs in `O(V * E * log(V))` time,
+/// which is faster than `floyd_warshall` for sparse graphs.
+///
+/// Returns a `NegativeCycle` error if the graph contains a cycle with negative total weight.
+pub fn johnson<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  weight_fn: F,
+) -> Result<DistanceMatrix<'a, VId, W>, NegativeCycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight + Sub<Output = W>,
+{
+  let zero = W::default();
+  let vids = g.all_vids();
+
+  // same as running Bellman-Ford from a virtual vertex connected to all other vertices by zero-weight edges
+  let virtual_source = ShortestPaths {
+    distances: vids.iter().map(|&vid| (vid, zero)).collect(),
+    parents: HashMap::new(),
+  };
+  let potentials = relax_edges(g, virtual_source, &weight_fn)?.distances;
+
+  let reweighted = |from: &'a VId, to: &'a VId, edge: &'a E| weight_fn(edge) + potentials[from] - potentials[to];
+
+  let mut matrix = DistanceMatrix::new(vids);
+  for (from_idx, &from) in matrix.vids.clone().iter().enumerate() {
+    let result = dijkstra_until(g, from, reweighted, |_| false, &mut ());
+
+    for (to, distance) in result.distances {
+      let cell = matrix.at(from_idx, matrix.indices[to]);
+      matrix.distances[cell] = Some(distance - potentials[from] + potentials[to]);
+      matrix.predecessors[cell] = result.parents.get(to).map(|parent| matrix.indices[parent]);
+    }
+  }
+
+  Ok(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::shortest_paths::bellman_ford;
+
+  /// A -4-> B, A -2-> C, B -(-3)-> C, C -2-> D, B -5-> D, D -3-> A, and an isolated E
+  fn graph_with_negative_edge() -> Graph<&'static str, i32> {
+    let mut g = Graph::new();
+    for vid in ["A", "B", "C", "D", "E"] {
+      g.push_vid(vid);
+    }
+    for (from, to, weight) in [
+      ("A", "B", 4),
+      ("A", "C", 2),
+      ("B", "C", -3),
+      ("C", "D", 2),
+      ("B", "D", 5),
+      ("D", "A", 3),
+    ] {
+      g.push_edge(from, to, weight);
+    }
+
+    g
+  }
+
+  #[test]
+  fn all_pairs_test() {
+    let g = graph_with_negative_edge();
+
+    for matrix in [floyd_warshall(&g, |w| *w).unwrap(), johnson(&g, |w| *w).unwrap()] {
+      assert_eq!(matrix.vertices().len(), 5);
+
+      // agrees with single source shortest paths
+      for &from in matrix.vertices() {
+        let paths = bellman_ford(&g, from, |w| *w).unwrap();
+        for &to in matrix.vertices() {
+          assert_eq!(matrix.distance(from, to), paths.distances.get(to).copied());
+          assert_eq!(matrix[(from, to)], paths.distances.get(to).copied());
+        }
+      }
+
+      assert_eq!(matrix.distance(&"A", &"D"), Some(3));
+      let path = matrix.path(&g, &"A", &"D", |w| *w).unwrap();
+      assert_eq!((path.vertices(), path.cost()), (&[&"A", &"B", &"C", &"D"][..], 3));
+      assert_eq!(path.edges().iter().copied().sum::<i32>(), 3);
+      let path = matrix.path(&g, &"D", &"C", |w| *w).unwrap();
+      assert_eq!(path.vertices(), [&"D", &"A", &"B", &"C"]);
+      assert_eq!(matrix.path(&g, &"C", &"C", |w| *w), Some(Path::new(&"C")));
+      assert_eq!(matrix.path(&g, &"A", &"E", |w| *w), None);
+      assert_eq!(matrix.distance(&"A", &"Z"), None);
+    }
+  }
+
+  #[test]
+  fn all_pairs_negative_cycle_test() {
+    let mut g = graph_with_negative_edge();
+    g.push_edge("D", "B", -1);
+
+    for result in [floyd_warshall(&g, |w| *w), johnson(&g, |w| *w)] {
+      let cycle = result.unwrap_err();
+      let len = cycle.vertices.len();
+      let weight = (0..len)
+        .map(|i| g.get_edge(cycle.vertices[i], cycle.vertices[(i + 1) % len]).unwrap())
+        .sum::<i32>();
+      assert!(weight < 0);
+    }
+  }
+}
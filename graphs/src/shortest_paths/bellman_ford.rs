@@ -0,0 +1,242 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use super::{NegativeCycle, ShortestPaths};
+use crate::search::Weight;
+use crate::Graph;
+
+/// Finds the shortest paths from `start` using the Bellman-Ford algorithm, with `weight_fn`
+/// giving the weight of each edge. Unlike `dijkstra`, weights can be negative.
+///
+/// Returns a `NegativeCycle` error if there's a cycle with negative total weight reachable from `start`.
+///
+/// Runs in `O(V * E)` time, but stops as soon as a round of relaxations doesn't improve anything.
+pub fn bellman_ford<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+) -> Result<ShortestPaths<'a, VId, W>, NegativeCycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let mut paths = ShortestPaths {
+    distances: HashMap::new(),
+    parents: HashMap::new(),
+  };
+  paths.distances.insert(start, W::default());
+
+  relax_edges(g, paths, weight_fn)
+}
+
+/// Runs Bellman-Ford relaxation rounds, starting with the distances already known in `paths`.
+pub(crate) fn relax_edges<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  mut paths: ShortestPaths<'a, VId, W>,
+  weight_fn: F,
+) -> Result<ShortestPaths<'a, VId, W>, NegativeCycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let vertex_count = g.all_vids().len();
+
+  // without negative cycles, each shortest path has at most `vertex_count - 1` edges,
+  // so the `vertex_count`th round can only relax something if there's a negative cycle.
+  for round in 0..vertex_count {
+    let mut last_relaxed = None;
+
+    for (from, to, edge) in g.iter_complete_edges() {
+      if let Some(&from_distance) = paths.distances.get(from) {
+        let distance = from_distance + weight_fn(edge);
+
+        let improves = match paths.distances.get(to) {
+          None => true,
+          Some(&known_distance) => distance < known_distance,
+        };
+        if improves {
+          paths.distances.insert(to, distance);
+          paths.parents.insert(to, from);
+          last_relaxed = Some(to);
+        }
+      }
+    }
+
+    match last_relaxed {
+      None => return Ok(paths),
+      Some(vid) => {
+        if round + 1 == vertex_count {
+          return Err(trace_cycle(&paths.parents, vid));
+        }
+      }
+    }
+  }
+
+  Ok(paths)
+}
+
+/// Shortest Path Faster Algorithm: a queue-based version of `bellman_ford`, which only relaxes
+/// the edges going out of vertices whose distance has just improved.
+///
+/// Has the same worst case complexity, but is usually much faster on sparse graphs.
+pub fn spfa<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  start: &'a VId,
+  weight_fn: F,
+) -> Result<ShortestPaths<'a, VId, W>, NegativeCycle<&'a VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  let vertex_count = g.all_vids().len();
+  let mut paths = ShortestPaths {
+    distances: HashMap::new(),
+    parents: HashMap::new(),
+  };
+  paths.distances.insert(start, W::default());
+  // number of edges on the current shortest path to each vertex
+  let mut edge_counts = HashMap::new();
+  edge_counts.insert(start, 0);
+
+  let mut queue = VecDeque::new();
+  let mut queued = HashSet::new();
+  queue.push_back(start);
+  queued.insert(start);
+
+  while let Some(from) = queue.pop_front() {
+    queued.remove(from);
+    let from_distance = paths.distances[from];
+    let from_edge_count = edge_counts[from];
+
+    for (to, edge) in g.incident_edges(from).into_iter().flatten() {
+      let distance = from_distance + weight_fn(edge);
+
+      let improves = match paths.distances.get(to) {
+        None => true,
+        Some(&known_distance) => distance < known_distance,
+      };
+      if improves {
+        paths.distances.insert(to, distance);
+        paths.parents.insert(to, from);
+
+        // a shortest path can't have more than `vertex_count - 1` edges without a negative cycle
+        edge_counts.insert(to, from_edge_count + 1);
+        if from_edge_count + 1 >= vertex_count {
+          return Err(trace_cycle(&paths.parents, to));
+        }
+
+        if queued.insert(to) {
+          queue.push_back(to);
+        }
+      }
+    }
+  }
+
+  Ok(paths)
+}
+
+/// Extracts a negative cycle from the `parents` map, starting from a vertex `vid` that was relaxed
+/// after all shortest paths should have been found: walking back along the parents from it
+/// is guaranteed to end up on the cycle.
+fn trace_cycle<'a, VId>(parents: &HashMap<&'a VId, &'a VId>, vid: &'a VId) -> NegativeCycle<&'a VId>
+where
+  VId: Eq + Hash,
+{
+  let mut seen = HashSet::new();
+  let mut on_cycle = vid;
+  while seen.insert(on_cycle) {
+    on_cycle = parents[on_cycle];
+  }
+
+  let mut vertices = vec![on_cycle];
+  let mut curr = parents[on_cycle];
+  while curr != on_cycle {
+    vertices.push(curr);
+    curr = parents[curr];
+  }
+
+  // parents point backwards along the cycle
+  vertices.reverse();
+  NegativeCycle { vertices }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Edges are costs, negative ones are profits:
+  /// A -4-> B, A -2-> C, B -(-3)-> C, C -2-> D, B -5-> D, E -1-> A
+  fn profits_graph() -> Graph<&'static str, i32> {
+    let mut g = Graph::new();
+    for vid in ["A", "B", "C", "D", "E"] {
+      g.push_vid(vid);
+    }
+    for (from, to, weight) in [
+      ("A", "B", 4),
+      ("A", "C", 2),
+      ("B", "C", -3),
+      ("C", "D", 2),
+      ("B", "D", 5),
+      ("E", "A", 1),
+    ] {
+      g.push_edge(from, to, weight);
+    }
+
+    g
+  }
+
+  #[test]
+  fn bellman_ford_test() {
+    let g = profits_graph();
+
+    for paths in [bellman_ford(&g, &"A", |w| *w).unwrap(), spfa(&g, &"A", |w| *w).unwrap()] {
+      assert_eq!(paths.distances.len(), 4);
+      assert_eq!(paths.distances[&"C"], 1);
+      assert_eq!(paths.distances[&"D"], 3);
+      let path = paths.path_to(&g, &"D", |w| *w).unwrap();
+      assert_eq!(path.vertices(), [&"A", &"B", &"C", &"D"]);
+      assert_eq!(path.edges().iter().copied().sum::<i32>(), path.cost());
+      assert_eq!(path.cost(), 3);
+      assert_eq!(paths.path_to(&g, &"E", |w| *w), None);
+    }
+  }
+
+  #[test]
+  fn negative_cycle_test() {
+    let mut g = profits_graph();
+    // closes a cycle through A, C (maybe via B), D and E with a big profit
+    g.push_edge("D", "E", -10);
+
+    for result in [
+      bellman_ford(&g, &"A", |w| *w),
+      spfa(&g, &"A", |w| *w),
+      spfa(&g, &"E", |w| *w),
+    ] {
+      let cycle = result.unwrap_err();
+      let len = cycle.vertices.len();
+
+      // the cycle is reported in the order of its edges, and its weight is negative
+      let weight = (0..len)
+        .map(|i| g.get_edge(cycle.vertices[i], cycle.vertices[(i + 1) % len]).unwrap())
+        .sum::<i32>();
+      assert!(weight < 0);
+      assert!(cycle.vertices.contains(&&"D"));
+      assert!(cycle.vertices.contains(&&"E"));
+    }
+
+    // the cycle doesn't matter for the vertices that can't reach it
+    g.push_edge("D", "F", -1);
+    assert!(bellman_ford(&g, &"F", |w| *w).is_ok());
+
+    let cycle = NegativeCycle {
+      vertices: vec!["A", "C", "D", "E"],
+    };
+    assert_eq!(cycle.to_string(), r#"negative cycle: "A" "C" "D" "E" "A""#);
+  }
+}
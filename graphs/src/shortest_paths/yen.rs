@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::hash::Hash;
+
+use crate::search::{dijkstra, Control, Visitor, Weight};
+use crate::{Graph, Path};
+
+/// Finds up to `k` shortest loopless paths from `source` to `target` using Yen's algorithm,
+/// with `weight_fn` giving the weight of each edge. Weights must not be negative.
+///
+/// Returns the paths with their weights as the costs, in ascending order of weight.
+/// No path repeats a vertex, and no two paths have the same sequence of vertices: out of
+/// parallel edges, only the cheapest one is used.
+///
+/// See `KShortestPaths` for a lazy version, which allows to stop once a good enough path is found.
+pub fn k_shortest_paths<'a, VId, E, V, F, W>(
+  g: &'a Graph<VId, E, V>,
+  source: &'a VId,
+  target: &'a VId,
+  k: usize,
+  weight_fn: F,
+) -> Vec<Path<&'a VId, &'a E, W>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  KShortestPaths::new(g, source, target, weight_fn).take(k).collect()
+}
+
+/// An iterator over loopless paths from a source to a target, in ascending order of weight.
+///
+/// Each path is only computed when requested, so the iterator can be stopped as soon as
+/// an acceptable path is found. Finding the next path takes one Dijkstra run per vertex
+/// of the previous path.
+pub struct KShortestPaths<'a, VId, E, V, F, W> {
+  g: &'a Graph<VId, E, V>,
+  source: &'a VId,
+  target: &'a VId,
+  weight_fn: F,
+  // paths returned so far; the last one is where the next path deviates from
+  found: Vec<Vec<&'a VId>>,
+  exhausted: bool,
+  // all paths that were either returned or are waiting in `candidates`
+  seen: HashSet<Vec<&'a VId>>,
+  candidates: BinaryHeap<Candidate<'a, VId, W>>,
+  // `candidates` are numbered to be returned in the order they were found when their weights are equal
+  next_seq: usize,
+}
+
+impl<'a, VId, E, V, F, W> KShortestPaths<'a, VId, E, V, F, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  pub fn new(g: &'a Graph<VId, E, V>, source: &'a VId, target: &'a VId, weight_fn: F) -> Self {
+    KShortestPaths {
+      g,
+      source,
+      target,
+      weight_fn,
+      found: vec![],
+      exhausted: false,
+      seen: HashSet::new(),
+      candidates: BinaryHeap::new(),
+      next_seq: 0,
+    }
+  }
+
+  /// The cheapest edge from `from` to `to`, with its weight.
+  fn step(&self, from: &'a VId, to: &'a VId) -> (&'a E, W) {
+    self
+      .g
+      .incident_edges(from)
+      .into_iter()
+      .flatten()
+      .filter(|(next, _edge)| next == to)
+      .map(|(_next, edge)| (edge, (self.weight_fn)(edge)))
+      .min_by_key(|&(_edge, weight)| weight)
+      .expect("consecutive vertices of a path must be connected")
+  }
+
+  /// Finds the shortest path from `from` to the target, avoiding the vertices & edges in `removed`.
+  fn spur_path(&self, from: &'a VId, removed: &mut Removed<'a, VId>) -> Option<(Vec<&'a VId>, W)> {
+    let result = dijkstra(self.g, from, &self.weight_fn, Some(self.target), removed);
+    let (target, weight) = result.found?;
+    Some((result.path_to(self.g, target, &self.weight_fn)?.into_vertices(), weight))
+  }
+
+  /// Adds the deviations from the `last` path to the candidates.
+  fn push_deviations(&mut self, last: &[&'a VId]) {
+    let mut root_weight = W::default();
+
+    for spur_idx in 0..last.len() - 1 {
+      let (root, spur) = (&last[..=spur_idx], last[spur_idx]);
+      let mut removed = Removed {
+        vertices: root[..spur_idx].iter().copied().collect(),
+        edges: HashSet::new(),
+      };
+
+      // the next edges of the known paths sharing the same root were already taken
+      for path in &self.found {
+        if path.len() > spur_idx + 1 && &path[..=spur_idx] == root {
+          removed.edges.insert((path[spur_idx], path[spur_idx + 1]));
+        }
+      }
+
+      if let Some((spur_path, spur_weight)) = self.spur_path(spur, &mut removed) {
+        let mut path = root[..spur_idx].to_vec();
+        path.extend(spur_path);
+
+        if !self.seen.contains(&path) {
+          self.seen.insert(path.clone());
+          self.candidates.push(Candidate {
+            path,
+            weight: root_weight + spur_weight,
+            seq: self.next_seq,
+          });
+          self.next_seq += 1;
+        }
+      }
+
+      root_weight = root_weight + self.step(spur, last[spur_idx + 1]).1;
+    }
+  }
+}
+
+impl<'a, VId, E, V, F, W> Iterator for KShortestPaths<'a, VId, E, V, F, W>
+where
+  VId: Eq + Hash,
+  V: Hash,
+  F: Fn(&'a E) -> W,
+  W: Weight,
+{
+  type Item = Path<&'a VId, &'a E, W>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.exhausted {
+      return None;
+    }
+
+    let next = match self.found.last().cloned() {
+      None => self.spur_path(self.source, &mut Removed::default()),
+      Some(last) => {
+        self.push_deviations(&last);
+        self
+          .candidates
+          .pop()
+          .map(|Candidate { path, weight, .. }| (path, weight))
+      }
+    };
+
+    match next {
+      None => {
+        self.exhausted = true;
+        None
+      }
+      Some((path, weight)) => {
+        self.seen.insert(path.clone());
+        self.found.push(path.clone());
+
+        let edges = path.windows(2).map(|step| self.step(step[0], step[1]).0).collect();
+        Some(Path::from_parts(path, edges, weight))
+      }
+    }
+  }
+}
+
+/// Prunes the removed vertices & edges from a search.
+struct Removed<'a, VId> {
+  vertices: HashSet<&'a VId>,
+  edges: HashSet<(&'a VId, &'a VId)>,
+}
+
+impl<'a, VId> Default for Removed<'a, VId> {
+  fn default() -> Self {
+    Removed {
+      vertices: HashSet::new(),
+      edges: HashSet::new(),
+    }
+  }
+}
+
+impl<'a, VId> Visitor<VId> for Removed<'a, VId>
+where
+  VId: Eq + Hash,
+{
+  fn examine_edge(&mut self, from: &VId, to: &VId) -> Control {
+    if self.vertices.contains(to) || self.edges.contains(&(from, to)) {
+      Control::Prune
+    } else {
+      Control::Continue
+    }
+  }
+}
+
+/// A path waiting in the candidates' heap, ordered by reversed weight and then by reversed
+/// sequence number, since `BinaryHeap` is a max-heap.
+struct Candidate<'a, VId, W> {
+  path: Vec<&'a VId>,
+  weight: W,
+  seq: usize,
+}
+
+impl<'a, VId, W: Ord> Ord for Candidate<'a, VId, W> {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.weight.cmp(&self.weight).then_with(|| other.seq.cmp(&self.seq))
+  }
+}
+
+impl<'a, VId, W: Ord> PartialOrd for Candidate<'a, VId, W> {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl<'a, VId, W: Ord> PartialEq for Candidate<'a, VId, W> {
+  fn eq(&self, other: &Self) -> bool {
+    self.cmp(other) == Ordering::Equal
+  }
+}
+
+impl<'a, VId, W: Ord> Eq for Candidate<'a, VId, W> {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The example graph from the Wikipedia's article on Yen's algorithm.
+  fn roads() -> Graph<&'static str, u32> {
+    let mut g = Graph::new();
+    for vid in ["C", "D", "E", "F", "G", "H"] {
+      g.push_vid(vid);
+    }
+    for (from, to, weight) in [
+      ("C", "D", 3),
+      ("C", "E", 2),
+      ("D", "F", 4),
+      ("E", "D", 1),
+      ("E", "F", 2),
+      ("E", "G", 3),
+      ("F", "G", 2),
+      ("F", "H", 1),
+      ("G", "H", 2),
+    ] {
+      g.push_edge(from, to, weight);
+    }
+
+    g
+  }
+
+  #[test]
+  fn k_shortest_paths_test() {
+    let g = roads();
+
+    let paths = k_shortest_paths(&g, &"C", &"H", 3, |w| *w);
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[0].vertices(), [&"C", &"E", &"F", &"H"]);
+    assert_eq!(paths[0].cost(), 5);
+    assert_eq!(paths[1].vertices(), [&"C", &"E", &"G", &"H"]);
+    assert_eq!(paths[1].edges(), [&2, &3, &2]);
+    assert_eq!(paths[2].cost(), 8);
+
+    // the iterator goes through all loopless paths in ascending order of weight
+    let all_paths = KShortestPaths::new(&g, &"C", &"H", |w| *w).collect::<Vec<_>>();
+    assert_eq!(
+      all_paths.iter().map(|path| path.cost()).collect::<Vec<_>>(),
+      vec![5, 7, 8, 8, 8, 11, 11]
+    );
+    for path in &all_paths {
+      assert_eq!((path.start(), path.end()), (&&"C", &&"H"));
+      assert_eq!(path.iter().collect::<HashSet<_>>().len(), path.len() + 1);
+      assert_eq!(path.edges().iter().copied().sum::<u32>(), path.cost());
+    }
+    let distinct = all_paths.iter().map(|path| path.vertices()).collect::<HashSet<_>>();
+    assert_eq!(distinct.len(), all_paths.len());
+
+    assert_eq!(k_shortest_paths(&g, &"H", &"C", 3, |w| *w), vec![]);
+    assert_eq!(k_shortest_paths(&g, &"C", &"C", 3, |w| *w), vec![Path::new(&"C")]);
+  }
+}
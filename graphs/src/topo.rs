@@ -0,0 +1,260 @@
+//! Topological sorting: ordering the vertices of a directed acyclic graph so that each edge
+//! goes from an earlier vertex to a later one. E.g., if the edges point from a task to the tasks
+//! that depend on it, the order is a valid schedule.
+//!
+//! If the graph has a cycle, there is no such order, and a `Cycle` error is returned instead.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+
+use crate::Graph;
+
+/// Returned when a graph can't be sorted topologically, because it has a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<VId> {
+  /// Vertex ids along one of the graph's cycles, in the order of its edges. The edge from the last vertex
+  /// back to the first one closes the cycle.
+  pub vertices: Vec<VId>,
+}
+
+impl<VId: fmt::Debug> fmt::Display for Cycle<VId> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "cycle:")?;
+    for vid in self.vertices.iter().chain(self.vertices.first()) {
+      write!(f, " {:?}", vid)?;
+    }
+    Ok(())
+  }
+}
+
+impl<VId: fmt::Debug> Error for Cycle<VId> {}
+
+/// Sorts the vertices of `g` topologically using Kahn's algorithm: repeatedly takes a vertex
+/// without incoming edges from the vertices that aren't sorted yet.
+///
+/// Returns the vertex ids in an order where each vertex comes before all vertices its edges lead to,
+/// or one of the cycles preventing that.
+pub fn toposort<VId, E, V>(g: &Graph<VId, E, V>) -> Result<Vec<&VId>, Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  Ok(topo_layers(g)?.into_iter().flatten().collect())
+}
+
+/// Sorts the vertices of `g` topologically using depth-first search: a vertex is finished after
+/// all vertices reachable from it, so the reversed order of finishing is topological.
+///
+/// Returns the same as `toposort`, though the order can differ, since a graph can have many of them.
+pub fn toposort_dfs<VId, E, V>(g: &Graph<VId, E, V>) -> Result<Vec<&VId>, Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let mut finished = HashSet::new();
+  let mut order = vec![];
+
+  for root in g.all_vids() {
+    if finished.contains(root) {
+      continue;
+    }
+
+    // the current path, with the index of the next edge to follow from each vertex
+    let mut path = vec![(root, 0)];
+    let mut on_path = HashSet::from([root]);
+    while let Some((curr, next_edge)) = path.last_mut() {
+      let curr = *curr;
+      match g.incident_edges(curr).and_then(|edges| edges.get(*next_edge)) {
+        None => {
+          path.pop();
+          on_path.remove(curr);
+          finished.insert(curr);
+          order.push(curr);
+        }
+        Some((next, _edge)) => {
+          *next_edge += 1;
+
+          if on_path.contains(next) {
+            let start = path.iter().position(|&(vid, _)| vid == next).unwrap();
+            let vertices = path[start..].iter().map(|&(vid, _)| vid).collect();
+            return Err(Cycle { vertices });
+          }
+          if !finished.contains(next) {
+            on_path.insert(next);
+            path.push((next, 0));
+          }
+        }
+      }
+    }
+  }
+
+  order.reverse();
+  Ok(order)
+}
+
+/// Groups the vertices of `g` into layers: the first one has the vertices without incoming edges,
+/// and each next one has the vertices whose incoming edges all come from the previous layers.
+/// When edges lead from each vertex to the ones depending on it, the vertices of a layer don't depend on
+/// each other, so they can be processed in parallel once the previous layers are.
+///
+/// Returns the layers, or one of the cycles preventing a topological order.
+pub fn topo_layers<VId, E, V>(g: &Graph<VId, E, V>) -> Result<Vec<Vec<&VId>>, Cycle<&VId>>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let vids = g.all_vids();
+  let mut in_degrees = vids.iter().map(|&vid| (vid, 0)).collect::<HashMap<_, _>>();
+  for (_from, to, _edge) in g.iter_complete_edges() {
+    *in_degrees.get_mut(to).unwrap() += 1;
+  }
+
+  let mut layers = vec![];
+  let mut sorted = 0;
+  let mut layer = vids
+    .iter()
+    .copied()
+    .filter(|vid| in_degrees[vid] == 0)
+    .collect::<Vec<_>>();
+  while !layer.is_empty() {
+    let mut next_layer = vec![];
+    for &vid in &layer {
+      for next in g.adjacent(vid) {
+        let in_degree = in_degrees.get_mut(next).unwrap();
+        *in_degree -= 1;
+        if *in_degree == 0 {
+          next_layer.push(next);
+        }
+      }
+    }
+
+    sorted += layer.len();
+    layers.push(layer);
+    layer = next_layer;
+  }
+
+  if sorted < vids.len() {
+    return Err(find_cycle(g, &in_degrees));
+  }
+  Ok(layers)
+}
+
+/// Finds a cycle among the vertices Kahn's algorithm couldn't sort, i.e. the ones with a positive
+/// remaining in-degree. Each of them has an incoming edge from another one of them, so following
+/// those edges backwards must eventually repeat a vertex.
+fn find_cycle<'a, VId, E, V>(g: &'a Graph<VId, E, V>, in_degrees: &HashMap<&'a VId, usize>) -> Cycle<&'a VId>
+where
+  VId: Eq + Hash,
+  V: Hash,
+{
+  let mut predecessors = HashMap::new();
+  for (from, to, _edge) in g.iter_complete_edges() {
+    if in_degrees[from] > 0 && in_degrees[to] > 0 {
+      predecessors.entry(to).or_insert(from);
+    }
+  }
+
+  let mut curr = *predecessors
+    .keys()
+    .next()
+    .expect("unsorted vertices must have predecessors");
+  let mut walk = VecDeque::new();
+  let mut seen = HashMap::new();
+  while !seen.contains_key(curr) {
+    seen.insert(curr, walk.len());
+    walk.push_front(curr);
+    curr = predecessors[curr];
+  }
+
+  // the walk goes against the edges, so it's built from its end; the cycle is the part after `curr`
+  let cycle_len = walk.len() - seen[curr];
+  Cycle {
+    vertices: walk.into_iter().take(cycle_len).collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Tells if `order` contains each vertex of `g` once, and each edge goes forward in it.
+  fn is_topological<VId: Eq + Hash>(g: &Graph<VId>, order: &[&VId]) -> bool {
+    let positions = order
+      .iter()
+      .enumerate()
+      .map(|(idx, &vid)| (vid, idx))
+      .collect::<HashMap<_, _>>();
+    positions.len() == g.all_vids().len()
+      && g
+        .iter_complete_edges()
+        .all(|(from, to, _)| positions[from] < positions[to])
+  }
+
+  /// Tells if `cycle` is a cycle of `g`.
+  fn is_cycle<VId: Eq + Hash>(g: &Graph<VId>, cycle: &Cycle<&VId>) -> bool {
+    let vertices = &cycle.vertices;
+    !vertices.is_empty()
+      && (0..vertices.len()).all(|idx| {
+        let next = vertices[(idx + 1) % vertices.len()];
+        g.adjacent(vertices[idx]).contains(&next)
+      })
+  }
+
+  fn clothes() -> Graph<&'static str> {
+    let mut g = Graph::new();
+    for (from, to) in [
+      ("underwear", "pants"),
+      ("underwear", "shoes"),
+      ("pants", "belt"),
+      ("pants", "shoes"),
+      ("shirt", "belt"),
+      ("shirt", "tie"),
+      ("tie", "jacket"),
+      ("belt", "jacket"),
+      ("socks", "shoes"),
+    ] {
+      g.push_edge(from, to, ());
+    }
+    g.push_vid("watch");
+    g
+  }
+
+  #[test]
+  fn toposort_test() {
+    let g = clothes();
+
+    for order in [toposort(&g).unwrap(), toposort_dfs(&g).unwrap()] {
+      assert_eq!(order.len(), 9);
+      assert!(is_topological(&g, &order));
+    }
+
+    let layers = topo_layers(&g).unwrap();
+    assert_eq!(
+      layers.iter().map(|layer| layer.len()).collect::<Vec<_>>(),
+      vec![4, 2, 2, 1]
+    );
+    assert!(layers[0].contains(&&"watch"));
+    assert_eq!(layers[3], vec![&"jacket"]);
+  }
+
+  #[test]
+  fn cycle_test() {
+    let mut g = clothes();
+    g.push_edge("jacket", "shirt", ());
+    g.push_edge("shoes", "socks", ());
+
+    for cycle in [
+      toposort(&g).unwrap_err(),
+      toposort_dfs(&g).unwrap_err(),
+      topo_layers(&g).unwrap_err(),
+    ] {
+      assert!(is_cycle(&g, &cycle), "not a cycle: {}", cycle);
+    }
+
+    let mut g: Graph<&str> = Graph::new();
+    g.push_edge("A", "A", ());
+    assert_eq!(toposort(&g), Err(Cycle { vertices: vec![&"A"] }));
+    assert_eq!(toposort_dfs(&g).unwrap_err().to_string(), "cycle: \"A\" \"A\"");
+  }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod tests {
+  use graphs::all_paths::{all_paths, count_paths, VisitPolicy};
+  use graphs::Graph;
+
+  /// Advent of Code 2021, day 12: caves connected by undirected passages.
+  fn parse_caves(input: &str) -> Graph<&str> {
+    let mut caves = Graph::new();
+    for line in input.lines() {
+      let (from, to) = line.trim().split_once('-').unwrap();
+      caves.push_vid(from);
+      caves.push_vid(to);
+      caves.push_undirected_edge(from, to, ());
+    }
+
+    caves
+  }
+
+  /// Small (lowercase) caves can be visited once, big ones any number of times,
+  /// and a single small cave other than `start` and `end` can be visited `revisits` more times.
+  struct SmallCaves {
+    revisits: usize,
+  }
+
+  impl VisitPolicy<&str> for SmallCaves {
+    fn limit(&self, cave: &&str) -> Option<usize> {
+      if cave.chars().all(|c| c.is_ascii_uppercase()) {
+        None
+      } else {
+        Some(1)
+      }
+    }
+
+    fn exceptions(&self) -> usize {
+      self.revisits
+    }
+
+    fn allows_exception(&self, cave: &&str) -> bool {
+      *cave != "start" && *cave != "end"
+    }
+  }
+
+  const EXAMPLE: &str = "start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end";
+
+  #[test]
+  fn passage_pathing() {
+    let caves = parse_caves(EXAMPLE);
+
+    let paths = all_paths(&caves, &"start", &"end", SmallCaves { revisits: 0 })
+      .map(|path| path.into_vertices())
+      .collect::<Vec<_>>();
+    assert_eq!(paths.len(), 10);
+    assert!(paths.contains(&vec![&"start", &"A", &"c", &"A", &"b", &"A", &"end"]));
+    assert_eq!(count_paths(&caves, &"start", &"end", SmallCaves { revisits: 0 }), 10);
+
+    assert_eq!(
+      all_paths(&caves, &"start", &"end", SmallCaves { revisits: 1 }).count(),
+      36
+    );
+    assert_eq!(count_paths(&caves, &"start", &"end", SmallCaves { revisits: 1 }), 36);
+
+    let caves = parse_caves(include_str!("../../aoc_examples/inputs/2021d12"));
+    assert_eq!(count_paths(&caves, &"start", &"end", SmallCaves { revisits: 0 }), 4970);
+    assert_eq!(
+      count_paths(&caves, &"start", &"end", SmallCaves { revisits: 1 }),
+      137948
+    );
+  }
+}
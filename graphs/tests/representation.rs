@@ -1,6 +1,13 @@
 #[cfg(test)]
 mod tests {
+  use graphs::components::condensation;
+  use graphs::cycles::{elementary_cycles, girth, has_cycle};
+  use graphs::dependency::DependencyGraph;
+  use graphs::metrics::{double_sweep, eccentricities};
+  use graphs::shortest_paths::{floyd_warshall, johnson};
+  use graphs::topo::{topo_layers, toposort, toposort_dfs, Cycle};
   use graphs::Graph;
+  use std::collections::HashSet;
   use Direction::*;
 
   #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -48,6 +55,80 @@ mod tests {
     ubahn.push_undirected_edge("Leopoldplatz", "Wedding", u6_line);
   }
 
+  /// The network of `ubahn_representation`, for the tests querying it.
+  fn ubahn() -> Graph<&'static str, &'static str, Vec<&'static str>> {
+    let mut ubahn = Graph::new();
+    let (u6_line, u8_line, u9_line) = ("U6", "U8", "U9");
+
+    ubahn.push_vertex("Franz-Neumann-Platz", vec![u8_line]);
+    ubahn.push_vertex("Osloer Straße", vec![u8_line, u9_line]);
+    ubahn.push_vertex("Nauener Platz", vec![u9_line]);
+    ubahn.push_vertex("Pankstraße", vec![u8_line]);
+    ubahn.push_vertex("Leopoldplatz", vec![u6_line, u9_line]);
+    ubahn.push_vertex("Gesundbrunnen", vec![u8_line]);
+    ubahn.push_vertex("Wedding", vec![u6_line]);
+    ubahn.push_vertex("Seestraße", vec![u6_line]);
+    ubahn.push_vertex("Amrumer Straße", vec![u9_line]);
+
+    ubahn.push_undirected_edge("Franz-Neumann-Platz", "Osloer Straße", u8_line);
+    ubahn.push_undirected_edge("Osloer Straße", "Pankstraße", u8_line);
+    ubahn.push_undirected_edge("Pankstraße", "Gesundbrunnen", u8_line);
+
+    ubahn.push_undirected_edge("Osloer Straße", "Nauener Platz", u9_line);
+    ubahn.push_undirected_edge("Nauener Platz", "Leopoldplatz", u9_line);
+    ubahn.push_undirected_edge("Leopoldplatz", "Amrumer Straße", u9_line);
+
+    ubahn.push_undirected_edge("Seestraße", "Leopoldplatz", u6_line);
+    ubahn.push_undirected_edge("Leopoldplatz", "Wedding", u6_line);
+
+    ubahn
+  }
+
+  #[test]
+  fn ubahn_distances() {
+    let ubahn = ubahn();
+
+    for stops in [floyd_warshall(&ubahn, |_| 1).unwrap(), johnson(&ubahn, |_| 1).unwrap()] {
+      assert_eq!(stops.distance(&"Franz-Neumann-Platz", &"Wedding"), Some(4));
+      assert_eq!(stops[(&"Wedding", &"Gesundbrunnen")], Some(5));
+      let path = stops.path(&ubahn, &"Franz-Neumann-Platz", &"Wedding", |_| 1).unwrap();
+      assert_eq!(
+        path.vertices(),
+        [
+          &"Franz-Neumann-Platz",
+          &"Osloer Straße",
+          &"Nauener Platz",
+          &"Leopoldplatz",
+          &"Wedding"
+        ]
+      );
+      assert_eq!(path.len(), 4);
+      assert_eq!(path.edges(), [&"U8", &"U9", &"U9", &"U6"]);
+    }
+  }
+
+  #[test]
+  fn ubahn_metrics() {
+    let ubahn = ubahn();
+
+    let stops = eccentricities(&ubahn);
+    assert_eq!(stops.diameter(), Some(5));
+    assert_eq!(stops.radius(), Some(3));
+    assert_eq!(
+      stops.center().into_iter().collect::<HashSet<_>>(),
+      HashSet::from([&"Osloer Straße", &"Nauener Platz"])
+    );
+    assert_eq!(
+      stops.periphery().into_iter().collect::<HashSet<_>>(),
+      HashSet::from([&"Gesundbrunnen", &"Wedding", &"Seestraße", &"Amrumer Straße"])
+    );
+
+    // the network is a tree, so the double sweep finds the exact diameter
+    let (from, to, distance) = double_sweep(&ubahn, &"Leopoldplatz");
+    assert_eq!(distance, 5);
+    assert!(stops.periphery().contains(&from) && stops.periphery().contains(&to));
+  }
+
   #[derive(Debug, PartialEq, Eq, Hash)]
   enum Op {
     Sub,
@@ -100,4 +181,123 @@ mod tests {
     bindings.push_edge("x".to_string(), "y".to_string(), ());
     bindings.push_edge("y".to_string(), "x".to_string(), ());
   }
+
+  #[test]
+  fn bindings_toposort() {
+    // x = y - z, y = 2, z = 3; each edge goes from a binding to the one that uses it
+    let mut bindings: Graph<String, (), Expr> = Graph::new();
+    let x_expr = Apply {
+      op: Sub,
+      lhs: Box::new(Var("y".to_string())),
+      rhs: Box::new(Var("z".to_string())),
+    };
+    bindings.push_vertex("x".to_string(), x_expr);
+    bindings.push_vertex("y".to_string(), Num(2));
+    bindings.push_vertex("z".to_string(), Num(3));
+    bindings.push_edge("y".to_string(), "x".to_string(), ());
+    bindings.push_edge("z".to_string(), "x".to_string(), ());
+
+    assert!(!has_cycle(&bindings));
+    // x can be evaluated once y and z are, and those two don't depend on anything
+    let order = toposort_dfs(&bindings).unwrap();
+    assert_eq!(order[2], "x");
+    let layers = topo_layers(&bindings).unwrap();
+    assert_eq!(layers.len(), 2);
+    assert_eq!(
+      layers[0].iter().collect::<HashSet<_>>(),
+      HashSet::from([&&"y".to_string(), &&"z".to_string()])
+    );
+    assert_eq!(layers[1], vec!["x"]);
+  }
+
+  #[test]
+  fn bindings_cycles() {
+    // x = y * 2, y = x - 1
+    let mut bindings: Graph<String, (), Expr> = Graph::new();
+    let x_expr = Apply {
+      op: Mul,
+      lhs: Box::new(Var("y".to_string())),
+      rhs: Box::new(Num(2)),
+    };
+    let y_expr = Apply {
+      op: Sub,
+      lhs: Box::new(Var("x".to_string())),
+      rhs: Box::new(Num(1)),
+    };
+    bindings.push_vertex("x".to_string(), x_expr);
+    bindings.push_vertex("y".to_string(), y_expr);
+    bindings.push_edge("x".to_string(), "y".to_string(), ());
+    bindings.push_edge("y".to_string(), "x".to_string(), ());
+
+    // x and y depend on each other, so there is no order to evaluate them in
+    let cycle = toposort(&bindings).unwrap_err();
+    assert_eq!(cycle.vertices.len(), 2);
+    assert!(cycle.vertices.contains(&&"x".to_string()));
+    assert!(cycle.vertices.contains(&&"y".to_string()));
+    assert_eq!(toposort_dfs(&bindings).unwrap_err().vertices.len(), 2);
+    // and it's the only mutual recursion
+    assert!(has_cycle(&bindings));
+    let cycles = elementary_cycles(&bindings);
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(
+      cycles[0].vertices.iter().collect::<HashSet<_>>(),
+      HashSet::from([&&"x".to_string(), &&"y".to_string()])
+    );
+    assert_eq!(girth(&bindings), Some(2));
+    // but they can be evaluated together, as a single component
+    let components = condensation(&bindings);
+    assert_eq!(toposort(&components).unwrap().len(), 1);
+    assert!(components.iter_vertices().all(|(_, members)| members.len() == 2));
+  }
+
+  /// The variables used in `expr`.
+  fn vars(expr: &Expr) -> Vec<String> {
+    match expr {
+      Num(_) => vec![],
+      Var(name) => vec![name.clone()],
+      Apply { lhs, rhs, .. } => vars(lhs).into_iter().chain(vars(rhs)).collect(),
+    }
+  }
+
+  #[test]
+  fn bindings_dependencies() {
+    let mut bindings: DependencyGraph<String, Expr> = DependencyGraph::new();
+    let mut bind = |name: &str, expr: Expr| {
+      for var in vars(&expr) {
+        bindings.add_dependency(name.to_string(), var)?;
+      }
+      bindings.push_vertex(name.to_string(), expr);
+      Ok::<_, Cycle<String>>(())
+    };
+
+    // x = (2 - 7) * y, z = x - y, y = 3
+    let x_expr = Apply {
+      op: Mul,
+      lhs: Box::new(Apply {
+        op: Sub,
+        lhs: Box::new(Num(2)),
+        rhs: Box::new(Num(7)),
+      }),
+      rhs: Box::new(Var("y".to_string())),
+    };
+    let z_expr = Apply {
+      op: Sub,
+      lhs: Box::new(Var("x".to_string())),
+      rhs: Box::new(Var("y".to_string())),
+    };
+    bind("x", x_expr).unwrap();
+    bind("z", z_expr).unwrap();
+    bind("y", Num(3)).unwrap();
+    assert_eq!(bindings.order(), ["y", "x", "z"]);
+
+    // y = x * 3 would make x and y depend on each other
+    let cycle = bindings.add_dependency("y".to_string(), "x".to_string()).unwrap_err();
+    assert_eq!(cycle.vertices, ["x", "y"]);
+    assert_eq!(cycle.to_string(), r#"cycle: "x" "y" "x""#);
+    assert!(!bindings.depends_on(&"y".to_string(), &"x".to_string()));
+
+    // changing x only affects z, while changing y affects everything
+    assert_eq!(bindings.dirty(&"x".to_string()), [&"x", &"z"]);
+    assert_eq!(bindings.dirty(&"y".to_string()), [&"y", &"x", &"z"]);
+  }
 }
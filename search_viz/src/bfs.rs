@@ -5,6 +5,8 @@
 //! some for the animation, and the rest is reused across those + used to validate the solution in tests.
 use std::collections::{HashMap, HashSet, VecDeque};
 
+use graphs::Path;
+
 const ADJACENT_DELTA: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -57,26 +59,25 @@ pub fn bfs(fav_number: u32, start: Pos, goal: Pos) -> HashMap<Pos, Pos> {
 }
 
 /// Reconstructs path from `start` to `goal` using `parents` returned from `bfs`.
-/// Empty vector means that the `goal` wasn't reached.
-/// The `goal` itself is not included into the returned vector.
-pub fn reconstruct_path(parents: HashMap<Pos, Pos>, goal: Pos) -> Vec<Pos> {
-  let mut path = vec![];
+/// The path includes both `start` and `goal`; a path without any steps means that the `goal` wasn't reached.
+pub fn reconstruct_path(parents: HashMap<Pos, Pos>, goal: Pos) -> Path<Pos> {
+  let mut path = vec![goal];
   let mut curr = goal;
-
   while let Some(&parent) = parents.get(&curr) {
     path.push(parent);
     curr = parent;
   }
 
   path.reverse();
-  path
+  let len = path.len() - 1;
+  Path::from_vertices(path, len)
 }
 
 pub fn p1(fav_number: u32, start: Pos, goal: Pos) -> Option<usize> {
   let parents = bfs(fav_number, start, goal);
   let path = reconstruct_path(parents, goal);
 
-  if path.len() > 0 {
+  if !path.is_empty() {
     Some(path.len())
   } else {
     None
@@ -113,7 +114,7 @@ pub fn path_and_explored_by_generation_for_animation(
   fav_number: u32,
   start: Pos,
   goal: Pos,
-) -> (Vec<Pos>, HashMap<u32, Vec<Pos>>) {
+) -> (Path<Pos>, HashMap<u32, Vec<Pos>>) {
   // bfs
   let mut parents = HashMap::new();
   let mut queue = VecDeque::from(vec![(start, 0)]);
@@ -135,19 +136,10 @@ pub fn path_and_explored_by_generation_for_animation(
     }
   }
 
-  // reconstruct_path
-  let mut path = vec![];
-  let mut curr = goal;
-
-  while let Some(&parent) = parents.get(&curr) {
-    path.push(parent);
-    curr = parent;
-  }
-
-  path.reverse();
+  let path = reconstruct_path(parents, goal);
 
   // invert explored & remove path elements from it
-  let path_set: HashSet<Pos> = HashSet::from_iter(path.iter().cloned());
+  let path_set: HashSet<Pos> = HashSet::from_iter(path.vertices().iter().cloned());
   let mut inv_explored: HashMap<u32, Vec<Pos>> = HashMap::new();
   for (pos, gen) in explored {
     if !path_set.contains(&pos) {
@@ -203,8 +195,8 @@ mod test {
 
     let path = reconstruct_path(parents, goal);
     assert_eq!(
-      path,
-      vec![
+      path.vertices(),
+      [
         Pos { x: 1, y: 1 },
         Pos { x: 1, y: 2 },
         Pos { x: 2, y: 2 },
@@ -216,17 +208,55 @@ mod test {
         Pos { x: 5, y: 5 },
         Pos { x: 6, y: 5 },
         Pos { x: 7, y: 5 },
+        Pos { x: 7, y: 4 },
       ]
     );
     assert_eq!(path.len(), 11);
   }
 
+  #[test]
+  fn path_and_explored_by_generation_for_animation_test() {
+    let (start, goal) = (Pos { x: 1, y: 1 }, Pos { x: 7, y: 4 });
+    let (path, explored) = path_and_explored_by_generation_for_animation(10, start, goal);
+    assert_eq!(path, reconstruct_path(bfs(10, start, goal), goal));
+    assert!(explored.values().flatten().all(|pos| !path.vertices().contains(pos)));
+  }
+
   #[test]
   fn p1_test() {
     assert_eq!(p1(10, Pos { x: 1, y: 1 }, Pos { x: 7, y: 4 }), Some(11));
     assert_eq!(p1(1350, Pos { x: 1, y: 1 }, Pos { x: 31, y: 39 }), Some(92));
   }
 
+  #[test]
+  fn astar_agrees_with_bfs_test() {
+    use graphs::search::{astar_by, AStarOpts};
+
+    for (fav_number, goal, expected) in [(10, Pos { x: 7, y: 4 }, 11), (1350, Pos { x: 31, y: 39 }, 92)] {
+      let successors = |pos: &Pos| {
+        pos
+          .adjacent()
+          .filter(|next| next.is_open(fav_number))
+          .map(|next| (next, 1))
+          .collect::<Vec<_>>()
+      };
+      let manhattan = |pos: &Pos| pos.x.abs_diff(goal.x) + pos.y.abs_diff(goal.y);
+
+      let path = astar_by(
+        Pos { x: 1, y: 1 },
+        successors,
+        |pos| *pos == goal,
+        manhattan,
+        AStarOpts::default(),
+        &mut (),
+      )
+      .0
+      .unwrap();
+      assert_eq!(path.cost(), expected);
+      assert_eq!(path.len() as u32, expected);
+    }
+  }
+
   #[test]
   fn bfs2_test() {
     assert_eq!(bfs2(1350, Pos { x: 1, y: 1 }, 50), 124);
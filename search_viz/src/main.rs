@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::error::Error;
 
 use egui::{vec2, Color32, Frame, Margin, Rect, RichText, Sense, Slider, Stroke, TextEdit, Vec2};
+use graphs::Path;
 use instant::{Duration, Instant};
 
 mod bfs;
@@ -97,7 +98,7 @@ impl UserInput {
 pub struct TemplateApp {
   user_input: UserInput,
   validated: Validated,
-  path: Vec<Pos>,
+  path: Path<Pos>,
   explored: HashMap<u32, Vec<Pos>>,
   time: Instant,
   time_tick: u32,
@@ -270,7 +271,7 @@ impl TemplateApp {
     if self.show_path {
       // can be used for both path length and generation to show
       let elements_to_show = (self.path.len() as f32 / 100.0 * self.time_tick as f32).ceil() as usize;
-      for &pos in &self.path[..elements_to_show] {
+      for &pos in &self.path.vertices()[..elements_to_show] {
         if pos != self.validated.start && pos != self.validated.goal {
           let cell = logical_pos_to_screen_rect(pos, min_x, min_y);
           painter.rect_filled(cell, 6.0, PATH_COLOR);